@@ -0,0 +1,30 @@
+//! Counts what a scan finds with a handler, and prints failed requests from the event stream,
+//! e.g. `cargo run --example events -- https://crawler-test.com`
+use futures::StreamExt;
+use rnz::{Rinzler, ScanEvent};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[tokio::main]
+async fn main() {
+    let host = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "https://crawler-test.com".to_string());
+    let found = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&found);
+    let rinzler = Rinzler::builder()
+        .host(host)
+        .on_result(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        })
+        .build();
+    let mut events = Box::pin(rinzler.events());
+    while let Some(event) = events.next().await {
+        match event {
+            ScanEvent::Error(error) => println!("{} failed: {}", error.url, error.kind),
+            ScanEvent::Finished(outcome) => println!("{:?}", outcome),
+            _ => {}
+        }
+    }
+    println!("{} pages found", found.load(Ordering::Relaxed));
+}
@@ -0,0 +1,18 @@
+//! Crawls a site and prints each page found, e.g. `cargo run --example scan -- https://crawler-test.com`
+use futures::StreamExt;
+use rnz::Rinzler;
+
+#[tokio::main]
+async fn main() {
+    let host = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "https://crawler-test.com".to_string());
+    let rinzler = Rinzler::builder().host(host).threads(10).build();
+    let mut results = Box::pin(rinzler.scan());
+    while let Some(result) = results.next().await {
+        match result.status_code {
+            Some(status_code) => println!("{} {}", status_code, result.url),
+            None => println!("??? {}", result.url),
+        }
+    }
+}
@@ -1,63 +1,229 @@
-use crate::client::RinzlerClient;
-use crate::config::RinzlerSettings;
-use crate::crawler::rinzler_crawler::{ControllerMessage, ControllerMessageType, RinzlerCrawler};
-use crate::ui::rinzler_console::{ConsoleMessage, ConsoleMessageType, RinzlerConsole};
-use crossbeam::channel::{unbounded, Receiver, Sender};
-use rayon::ThreadPoolBuilder;
+use crate::client::host_limiter::HostLimiter;
+use crate::client::throttle::Throttle;
+use crate::client::{RequestOptions, RinzlerClient};
+use crate::config::{self, RinzlerSettings, FUZZ_KEYWORD};
+use crate::crawler::crawl_controller::{ControllerMessage, ControllerMessageType, CrawlController};
+use crate::crawler::crawl_frontier::CrawlFrontier;
+use crate::crawler::crawl_graph::CrawlGraph;
+use crate::crawler::crawl_progress::CrawlProgress;
+use crate::crawler::crawl_target::CrawlTarget;
+use crate::crawler::request_errors::RequestError;
+use crate::crawler::rinzler_crawler::RinzlerCrawler;
+use crate::crawler::security_finding::SecurityFindings;
+use crate::crawler::skipped_targets::SkippedTargets;
+use crate::metrics;
+use crate::ui::rinzler_console::{
+    ConsoleMessage, ConsoleMessageType, ConsoleSender, RinzlerConsole,
+};
+use crate::ui::sarif_report;
+use crate::ui::webhook::Webhook;
+use crossbeam::channel::{bounded, unbounded, Receiver, Sender};
+use futures::{stream, StreamExt};
 use std::error::Error;
+use std::io::{self, BufRead};
 use std::sync::{Arc, Mutex};
-use threadpool::ThreadPool;
+use std::thread::{self, JoinHandle};
+use tracing::{error, warn};
 use url::Url;
 
+// crawlers block once this many console messages are waiting, rather than buffering without limit
+pub(crate) const CONSOLE_BACKLOG: usize = 1024;
+
 pub(crate) struct RinzlerApplication {
     settings: RinzlerSettings,
 }
 
 impl RinzlerApplication {
     pub fn from_settings(settings: RinzlerSettings) -> RinzlerApplication {
-        ThreadPoolBuilder::new()
-            .thread_name(|i: usize| format!("rinzler-{}", i))
-            .num_threads(settings.max_threads)
-            .build_global()
-            .unwrap();
-
         RinzlerApplication { settings }
     }
 
-    pub async fn run(&self) -> Result<(), Box<dyn Error>> {
-        let (console_sender, console_receiver) = unbounded();
-        let settings = self.settings.clone();
-        let thread_pool = threadpool::ThreadPool::new(settings.max_threads);
+    pub async fn run(&self) -> Result<Arc<CrawlProgress>, Box<dyn Error>> {
+        let (console_sender, console_receiver) = bounded(CONSOLE_BACKLOG);
+        let mut settings = self.settings.clone();
 
-        RinzlerApplication::start_console(console_receiver, &thread_pool, settings.clone()).await?;
+        // the console pauses and resumes requests through the throttle they all wait on,
+        // and skips force browsing of a target by marking it in the skipped targets
+        let (throttle, skipped, progress) = RinzlerApplication::shared_state(&settings);
+        let console = RinzlerApplication::start_console(
+            console_receiver,
+            settings.clone(),
+            Arc::clone(&throttle),
+            Arc::clone(&skipped),
+            Arc::clone(&progress),
+        )?;
 
-        let mut controller_receivers = vec![];
-        let visited = Arc::new(Mutex::new(vec![]));
-        let scoped_domains: Vec<String> = settings
-            .hosts
-            .iter()
-            .map(|h| Url::parse(h).unwrap().domain().unwrap().to_string())
-            .collect();
+        if settings.preflight {
+            settings.hosts =
+                RinzlerApplication::preflight(&settings, &console_sender, Arc::clone(&throttle))
+                    .await;
+            if settings.hosts.is_empty() && !settings.stdin_urls {
+                let reason = "none of the targets could be reached".to_string();
+                RinzlerApplication::inform_console_to_exit(Err(reason.clone()), console_sender);
+                let _ = console.join();
+                return Err(reason.into());
+            }
+        }
+
+        let outcome = RinzlerApplication::scan(
+            settings,
+            console_sender.clone(),
+            throttle,
+            skipped,
+            Arc::clone(&progress),
+        )
+        .await;
+
+        RinzlerApplication::inform_console_to_exit(outcome.clone(), console_sender.clone());
+
+        let _ = console.join();
+        outcome?;
+        Ok(progress)
+    }
+
+    /// Sends one HEAD request to each target before any crawler starts, reporting whether it
+    /// answered, and returns the targets that did.
+    async fn preflight(
+        settings: &RinzlerSettings,
+        console_sender: &Sender<ConsoleMessage>,
+        throttle: Arc<Throttle>,
+    ) -> Vec<String> {
+        let host_limiter = Arc::new(HostLimiter::new(
+            settings.max_per_host,
+            settings.per_host_delay,
+        ));
+        // these requests aren't part of the scan, so they're kept out of its counts
+        let progress = Arc::new(CrawlProgress::new());
+        let client = RinzlerClient::new(settings, throttle, host_limiter, progress);
+        let checks: Vec<(CrawlTarget, Result<String, String>)> =
+            stream::iter(settings.hosts.clone())
+                .map(|host| {
+                    let client = client.clone();
+                    async move {
+                        let mut ct = CrawlTarget::new();
+                        ct.url = host.clone();
+                        // a fuzzed host only exists once the keyword is replaced, so it's let through
+                        if host.contains(FUZZ_KEYWORD) {
+                            return (ct, Ok("not checked".to_string()));
+                        }
+                        let outcome = client
+                            .send_head(&mut ct, RequestOptions::without_redirects())
+                            .await
+                            .map(|res| res.status().to_string())
+                            .map_err(|why| {
+                                let failure = RequestError::new(&host, &why);
+                                format!("{}: {}", failure.kind, failure.message)
+                            });
+                        ct.url = host;
+                        (ct, outcome)
+                    }
+                })
+                .buffered(settings.max_threads)
+                .collect()
+                .await;
+
+        let console_sender = ConsoleSender::new(console_sender.clone());
+        let mut reachable = vec![];
+        for (ct, outcome) in checks {
+            if outcome.is_ok() {
+                reachable.push(ct.url.clone());
+            }
+            console_sender
+                .send(ConsoleMessage {
+                    message_type: ConsoleMessageType::Preflight,
+                    data: outcome,
+                    original_target: None,
+                    crawl_target: Some(ct),
+                    total: None,
+                })
+                .await;
+        }
+        reachable
+    }
+
+    pub(crate) fn shared_state(
+        settings: &RinzlerSettings,
+    ) -> (Arc<Throttle>, Arc<SkippedTargets>, Arc<CrawlProgress>) {
+        let throttle = Arc::new(Throttle::new(
+            settings.rate_limit,
+            settings.requests_per_second,
+            settings.delay_jitter,
+            settings.adaptive_throttle,
+            settings.seed,
+            settings.delay_on_error,
+        ));
+        (
+            throttle,
+            Arc::new(SkippedTargets::new()),
+            Arc::new(CrawlProgress::new()),
+        )
+    }
+
+    /// Crawls every host until there's nothing left, reporting what it finds to `console_sender`,
+    /// then writes the reports that are made once the scan is over.
+    pub(crate) async fn scan(
+        settings: RinzlerSettings,
+        console_sender: Sender<ConsoleMessage>,
+        throttle: Arc<Throttle>,
+        skipped: Arc<SkippedTargets>,
+        progress: Arc<CrawlProgress>,
+    ) -> Result<String, String> {
+        let (controller_sender, controller_receiver) = unbounded();
+        let controller = CrawlController::new(controller_sender);
+        if let Some(addr) = settings.metrics_addr {
+            tokio::spawn(metrics::serve(addr, Arc::clone(&progress)));
+        }
+        let graph = Arc::new(CrawlGraph::new());
+        let security_findings = Arc::new(SecurityFindings::new());
+
+        let (webhook, webhook_task) = match &settings.webhook_url {
+            Some(url) => {
+                let (webhook, task) = Webhook::start(url.clone(), &settings.user_agent);
+                (Some(webhook), Some(task))
+            }
+            None => (None, None),
+        };
 
         RinzlerApplication::start_crawlers(
             settings.clone(),
             console_sender.clone(),
-            &thread_pool,
-            settings.hosts.clone(),
-            &mut controller_receivers,
-            visited,
-            scoped_domains.clone(),
+            controller,
+            throttle,
+            skipped,
+            Arc::clone(&progress),
+            Arc::clone(&graph),
+            Arc::clone(&security_findings),
+            webhook,
         );
 
-        let outcome = RinzlerApplication::wait_for_crawlers_to_finish(&mut controller_receivers);
+        let outcome = tokio::task::spawn_blocking(move || {
+            RinzlerApplication::wait_for_crawlers_to_finish(&controller_receiver)
+        })
+        .await
+        .unwrap_or_else(|why| Err(why.to_string()));
 
-        RinzlerApplication::inform_console_to_exit(outcome, console_sender.clone());
+        if let Some(path) = &settings.graph_output {
+            if let Err(why) = graph.write(path, settings.graph_format) {
+                error!("crawl graph couldn't be written to '{}': {}", path, why);
+            }
+        }
 
-        thread_pool.join();
-        Ok(())
+        if let Some(path) = &settings.output_sarif {
+            if let Err(why) = sarif_report::write(path, &security_findings.take()) {
+                error!("SARIF report couldn't be written to '{}': {}", path, why);
+            }
+        }
+
+        if let Some(task) = webhook_task {
+            let _ = task.await;
+        }
+        outcome
     }
 
-    fn inform_console_to_exit(reason: Result<String, String>, command_tx: Sender<ConsoleMessage>) {
+    pub(crate) fn inform_console_to_exit(
+        reason: Result<String, String>,
+        command_tx: Sender<ConsoleMessage>,
+    ) {
         let _ = command_tx.send(ConsoleMessage {
             message_type: ConsoleMessageType::Finish,
             data: reason,
@@ -68,86 +234,155 @@ impl RinzlerApplication {
     }
 
     fn wait_for_crawlers_to_finish(
-        controller_receivers: &mut Vec<Receiver<ControllerMessage>>,
+        controller_receiver: &Receiver<ControllerMessage>,
     ) -> Result<String, String> {
         let mut errors = vec![];
-        loop {
-            let finished = controller_receivers.iter_mut().all(|r| {
-                if let Ok(fin) = r.recv() {
-                    match fin.message_type {
-                        ControllerMessageType::FINISHED => true,
-                        ControllerMessageType::ERROR => {
-                            errors.push(fin.data);
-                            true
-                        }
-                    }
-                } else {
-                    false
-                }
-            });
-
-            if !errors.is_empty() {
-                break;
-            }
-            if finished {
-                break;
+        for message in controller_receiver.iter() {
+            match message.message_type {
+                ControllerMessageType::Finished => break,
+                ControllerMessageType::Error => errors.push(message.data),
             }
         }
 
         if errors.is_empty() {
             Ok("Scan Completed".to_string())
         } else {
-            let _ = format!("{}", errors.to_owned().join("\n")).as_str();
-            Err("Scan Failed".to_string())
+            Err(errors.join("\n"))
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn start_crawlers(
         settings: RinzlerSettings,
         console_sender: Sender<ConsoleMessage>,
-        thread_pool: &ThreadPool,
-        hosts: Vec<String>,
-        controller_receivers: &mut Vec<Receiver<ControllerMessage>>,
-        visited: Arc<Mutex<Vec<String>>>,
-        scoped_domains: Vec<String>,
+        controller: CrawlController,
+        throttle: Arc<Throttle>,
+        skipped: Arc<SkippedTargets>,
+        progress: Arc<CrawlProgress>,
+        graph: Arc<CrawlGraph>,
+        security_findings: Arc<SecurityFindings>,
+        webhook: Option<Webhook>,
     ) {
-        for target in hosts {
+        let visited = Arc::new(Mutex::new(settings.seen.iter().cloned().collect()));
+        let scoped_domains: Vec<String> = settings
+            .hosts
+            .iter()
+            .filter_map(|h| Some(Url::parse(h).ok()?.host_str()?.to_string()))
+            .collect();
+
+        let host_limiter = Arc::new(HostLimiter::new(
+            settings.max_per_host,
+            settings.per_host_delay,
+        ));
+        let client = RinzlerClient::new(&settings, throttle, host_limiter, Arc::clone(&progress));
+        let frontier = Arc::new(CrawlFrontier::new(settings.order, Arc::clone(&progress)));
+        let crawler_for = {
             let settings = settings.clone();
-            let (controller_sender, controller_receiver) = unbounded();
-            let console_sender = console_sender.clone();
-            let v = Arc::clone(&visited);
-            let scoped_domains = scoped_domains.clone();
-            thread_pool.execute(move || {
-                let rc = RinzlerClient::new(&settings.clone());
-                let crawler = RinzlerCrawler::new(
+            let frontier = Arc::clone(&frontier);
+            let controller = controller.clone();
+            let progress = Arc::clone(&progress);
+            let client = client.clone();
+            move |target: String, scoped_domains: Vec<String>| {
+                RinzlerCrawler::new(
                     target,
-                    settings,
-                    controller_sender,
-                    console_sender,
+                    settings.clone(),
+                    controller.clone(),
+                    console_sender.clone(),
                     scoped_domains,
-                    rc,
-                );
-                let result = crawler.crawl(v);
-                if let Ok(_result) = result {
-                    crawler.finish()
-                }
-            });
-            controller_receivers.push(controller_receiver);
+                    client.clone(),
+                    Arc::clone(&progress),
+                    Arc::clone(&graph),
+                    Arc::clone(&security_findings),
+                    webhook.clone(),
+                    Arc::clone(&frontier),
+                    Arc::clone(&skipped),
+                )
+            }
+        };
+        for target in settings.hosts.clone() {
+            frontier.push(crawler_for(target, scoped_domains.clone()));
+        }
+        if settings.stdin_urls {
+            RinzlerApplication::feed_from_stdin(
+                Arc::clone(&frontier),
+                controller,
+                move |mut target: String| {
+                    if let Some((origin, credentials)) = config::take_credentials(&mut target) {
+                        client.add_credentials(origin, credentials);
+                    }
+                    client.add_target(&target);
+                    // a target read from stdin is in scope alongside the ones given up front
+                    let mut scoped_domains = scoped_domains.clone();
+                    scoped_domains.extend(
+                        Url::parse(&target)
+                            .ok()
+                            .and_then(|url| url.host_str().map(|host| host.to_string())),
+                    );
+                    crawler_for(target, scoped_domains)
+                },
+            );
+        }
+        // each worker crawls one page at a time, so the workers bound how many branches are active
+        let workers = settings.max_crawlers.unwrap_or(settings.max_threads).max(1);
+        for _ in 0..workers {
+            let frontier = Arc::clone(&frontier);
+            let v = Arc::clone(&visited);
+            tokio::spawn(async move { frontier.work(v).await });
         }
     }
 
-    async fn start_console(
+    // targets are read on their own thread, as stdin blocks, and crawled as they arrive; the
+    // scan can't finish while it's still being read
+    fn feed_from_stdin<F>(frontier: Arc<CrawlFrontier>, controller: CrawlController, crawler_for: F)
+    where
+        F: Fn(String) -> RinzlerCrawler + Send + 'static,
+    {
+        let (target_sender, target_receiver) = unbounded();
+        frontier.start_feeding();
+        let feeding = controller.start();
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines().map_while(Result::ok) {
+                if target_sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        tokio::task::spawn_blocking(move || {
+            for line in target_receiver.iter() {
+                let target = line.trim();
+                if target.is_empty() {
+                    continue;
+                }
+                if let Err(why) = Url::parse(target) {
+                    warn!("Skipping target '{}' read from stdin: {}", target, why);
+                    continue;
+                }
+                frontier.push(crawler_for(target.to_string()));
+            }
+            frontier.stop_feeding();
+            drop(feeding);
+        });
+    }
+
+    fn start_console(
         console_receiver: Receiver<ConsoleMessage>,
-        thread_pool: &ThreadPool,
         settings: RinzlerSettings,
-    ) -> Result<(), Box<dyn Error>> {
-        let console = RinzlerConsole::new(settings.clone(), console_receiver)?;
-        thread_pool.execute(move || {
+        throttle: Arc<Throttle>,
+        skipped: Arc<SkippedTargets>,
+        progress: Arc<CrawlProgress>,
+    ) -> Result<JoinHandle<()>, Box<dyn Error>> {
+        let console = RinzlerConsole::new(
+            settings.clone(),
+            console_receiver,
+            throttle,
+            skipped,
+            progress,
+        )?;
+        Ok(thread::spawn(move || {
             console
                 .clear()
                 .banner(format!("{}", settings.clone()))
                 .render();
-        });
-        Ok(())
+        }))
     }
 }
@@ -0,0 +1,54 @@
+use crate::app::RinzlerApplication;
+use crate::client::replay::{RecordedRequest, Replay};
+use crate::config::parse_cmd_line;
+use crate::crawler::rinzler_crawler::RinzlerCrawler;
+use crate::ui::scan_diff::ScanDiff;
+use std::process;
+use tracing::error;
+
+/// Everything the `rnz` binary does, driven by the command line.
+pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let (settings, _log_guard) = parse_cmd_line();
+    if settings.dump_config {
+        print!("{}", settings);
+        return Ok(());
+    }
+    if let Some((old, new)) = &settings.diff {
+        let diff = ScanDiff::load(old, new).unwrap_or_else(|why| {
+            error!("scans couldn't be compared: {}", why);
+            process::exit(1);
+        });
+        println!("{}", diff.render(settings.diff_format));
+        return Ok(());
+    }
+    if let Some(transcript) = &settings.replay {
+        let requests = RecordedRequest::read(transcript).unwrap_or_else(|why| {
+            error!("'{}' couldn't be replayed: {}", transcript, why);
+            process::exit(1);
+        });
+        let replay = Replay::run(&settings, requests).await;
+        println!("{}", replay.render(settings.diff_format));
+        return Ok(());
+    }
+    if settings.dry_run {
+        let urls = RinzlerCrawler::planned_urls(&settings);
+        for url in &urls {
+            println!("{}", url);
+        }
+        eprintln!(
+            "{} requests would be sent, not counting soft 404 checks or pages found along the way",
+            urls.len()
+        );
+        return Ok(());
+    }
+    let fail_on = settings.fail_on.clone();
+    let fail_code = settings.fail_code;
+    let app = RinzlerApplication::from_settings(settings);
+    let progress = app.run().await?;
+    if let Some(fail_on) = fail_on {
+        if fail_on.is_reached(&progress) {
+            process::exit(fail_code);
+        }
+    }
+    Ok(())
+}
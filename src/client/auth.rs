@@ -0,0 +1,525 @@
+use crate::config::AuthType;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use md4::Md4;
+use md5::{Digest, Md5};
+use rand::Rng;
+use reqwest::header::{HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode, Url};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+pub(crate) type Credentials = (String, Option<String>);
+
+// seconds between 1601, where windows counts time from, and 1970
+const FILETIME_EPOCH_OFFSET: u64 = 11_644_473_600;
+// unicode, oem, request target, ntlm, always sign, extended session security
+const NTLM_FLAGS: u32 = 0x0008_8207;
+
+pub(crate) struct Authenticator {
+    auth_type: AuthType,
+    digests: Mutex<HashMap<String, DigestChallenge>>,
+    // NTLM authenticates a connection rather than a request, so each origin's requests take turns
+    // on a client that keeps just one connection to it, and a handshake's requests all share it
+    connection: Option<Client>,
+    turns: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    unanswered: Mutex<HashSet<String>>,
+}
+
+/// What to do about a 401 from an origin there are credentials for.
+pub(crate) enum Answer {
+    Resend,
+    Handshake(&'static str),
+    GiveUp,
+}
+
+impl Authenticator {
+    pub fn new(auth_type: AuthType, connection: Option<Client>) -> Authenticator {
+        Authenticator {
+            auth_type,
+            digests: Mutex::new(HashMap::new()),
+            connection,
+            turns: Mutex::new(HashMap::new()),
+            unanswered: Mutex::new(HashSet::new()),
+        }
+    }
+
+    // basic credentials go with every request, digest ones once the server has handed out a nonce
+    pub fn authorize(
+        &self,
+        request: RequestBuilder,
+        method: &Method,
+        url: &str,
+        credentials: &Credentials,
+    ) -> RequestBuilder {
+        match self.auth_type {
+            AuthType::Basic => request.basic_auth(&credentials.0, credentials.1.as_ref()),
+            AuthType::Digest => {
+                let url = match Url::parse(url) {
+                    Ok(url) => url,
+                    Err(_) => return request,
+                };
+                let mut digests = self.digests.lock().unwrap();
+                match digests.get_mut(&url.origin().ascii_serialization()) {
+                    Some(challenge) => {
+                        request.header(AUTHORIZATION, challenge.answer(method, &url, credentials))
+                    }
+                    None => request,
+                }
+            }
+            AuthType::Ntlm => request,
+        }
+    }
+
+    /// The client an origin's NTLM requests go through, and the turn they take on it.
+    pub fn connection(&self, url: &str) -> Option<(&Client, Arc<tokio::sync::Mutex<()>>)> {
+        let client = self.connection.as_ref()?;
+        let origin = Url::parse(url).ok()?.origin().ascii_serialization();
+        let turn = Arc::clone(self.turns.lock().unwrap().entry(origin).or_default());
+        Some((client, turn))
+    }
+
+    // a digest challenge is kept, so the resent request and every one after it answers it
+    pub fn answer(&self, challenged: &Response) -> Answer {
+        let offered = offered(challenged);
+        match self.auth_type {
+            AuthType::Digest => match offered.iter().find_map(|c| DigestChallenge::parse(c)) {
+                Some(challenge) => {
+                    let origin = challenged.url().origin().ascii_serialization();
+                    self.digests.lock().unwrap().insert(origin, challenge);
+                    Answer::Resend
+                }
+                None => self.unanswered(challenged, &offered, " (only MD5 digests are)"),
+            },
+            AuthType::Ntlm => match offered.iter().find_map(|c| ntlm_scheme(c)) {
+                Some(scheme) => Answer::Handshake(scheme),
+                None => self.unanswered(challenged, &offered, ""),
+            },
+            AuthType::Basic
+                if offered
+                    .iter()
+                    .any(|c| scheme_of(c).eq_ignore_ascii_case("basic")) =>
+            {
+                debug!("{} refused the basic auth credentials", challenged.url());
+                Answer::GiveUp
+            }
+            AuthType::Basic => self.unanswered(challenged, &offered, ""),
+        }
+    }
+
+    pub fn negotiate(scheme: &str) -> String {
+        format!("{} {}", scheme, BASE64.encode(ntlm::negotiate()))
+    }
+
+    // nothing is sent back when the connection turned out to be authenticated already
+    pub fn authenticate(
+        scheme: &str,
+        credentials: &Credentials,
+        negotiated: &Response,
+    ) -> Option<String> {
+        let challenge = offered(negotiated)
+            .iter()
+            .filter_map(|value| value.strip_prefix(scheme))
+            .filter_map(|token| BASE64.decode(token.trim()).ok())
+            .find_map(|token| ntlm::Challenge::parse(&token));
+        match challenge {
+            Some(challenge) => Some(format!(
+                "{} {}",
+                scheme,
+                BASE64.encode(ntlm::authenticate(credentials, &challenge))
+            )),
+            None if negotiated.status() != StatusCode::UNAUTHORIZED => None,
+            None => {
+                warn!("{} didn't send back an NTLM challenge", negotiated.url());
+                None
+            }
+        }
+    }
+
+    fn unanswered(&self, challenged: &Response, offered: &[String], note: &str) -> Answer {
+        let origin = challenged.url().origin().ascii_serialization();
+        if self.unanswered.lock().unwrap().insert(origin.clone()) {
+            let schemes: Vec<&str> = offered.iter().map(|c| scheme_of(c)).collect();
+            let schemes = match schemes.is_empty() {
+                true => "no known".to_string(),
+                false => schemes.join(", "),
+            };
+            warn!(
+                "{} asks for {} authentication, which --auth-type {} can't answer{}",
+                origin, schemes, self.auth_type, note
+            );
+        }
+        Answer::GiveUp
+    }
+}
+
+fn offered(res: &Response) -> Vec<String> {
+    res.headers()
+        .get_all(WWW_AUTHENTICATE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .collect()
+}
+
+fn scheme_of(challenge: &str) -> &str {
+    challenge.split_whitespace().next().unwrap_or_default()
+}
+
+fn ntlm_scheme(challenge: &str) -> Option<&'static str> {
+    match scheme_of(challenge).to_lowercase().as_str() {
+        "ntlm" => Some("NTLM"),
+        // negotiate falls back to ntlm when there's no kerberos ticket to offer
+        "negotiate" => Some("Negotiate"),
+        _ => None,
+    }
+}
+
+fn md5_hex(data: &str) -> String {
+    format!("{:x}", Md5::digest(data.as_bytes()))
+}
+
+struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    algorithm: String,
+    qop_auth: bool,
+    count: u32,
+}
+
+impl DigestChallenge {
+    // only md5 is answered, the sha-256 variants are reported as unsupported instead
+    fn parse(challenge: &str) -> Option<DigestChallenge> {
+        let (scheme, params) = challenge.split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("digest") {
+            return None;
+        }
+        let params = digest_params(params);
+        let algorithm = params
+            .get("algorithm")
+            .cloned()
+            .unwrap_or_else(|| "MD5".to_string());
+        if !algorithm.eq_ignore_ascii_case("md5") && !algorithm.eq_ignore_ascii_case("md5-sess") {
+            return None;
+        }
+        Some(DigestChallenge {
+            realm: params.get("realm").cloned().unwrap_or_default(),
+            nonce: params.get("nonce")?.clone(),
+            opaque: params.get("opaque").cloned(),
+            algorithm,
+            qop_auth: params
+                .get("qop")
+                .is_some_and(|qop| qop.split(',').any(|q| q.trim() == "auth")),
+            count: 0,
+        })
+    }
+
+    fn answer(&mut self, method: &Method, url: &Url, credentials: &Credentials) -> HeaderValue {
+        let cnonce = format!("{:016x}", rand::thread_rng().gen::<u64>());
+        self.answer_with(method, url, credentials, &cnonce)
+    }
+
+    fn answer_with(
+        &mut self,
+        method: &Method,
+        url: &Url,
+        credentials: &Credentials,
+        cnonce: &str,
+    ) -> HeaderValue {
+        let (username, password) = credentials;
+        let password = password.as_deref().unwrap_or_default();
+        self.count += 1;
+        let count = format!("{:08x}", self.count);
+        let uri = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+
+        let mut ha1 = md5_hex(&format!("{}:{}:{}", username, self.realm, password));
+        if self.algorithm.eq_ignore_ascii_case("md5-sess") {
+            ha1 = md5_hex(&format!("{}:{}:{}", ha1, self.nonce, cnonce));
+        }
+        let ha2 = md5_hex(&format!("{}:{}", method, uri));
+        let response = if self.qop_auth {
+            md5_hex(&format!(
+                "{}:{}:{}:{}:auth:{}",
+                ha1, self.nonce, count, cnonce, ha2
+            ))
+        } else {
+            md5_hex(&format!("{}:{}:{}", ha1, self.nonce, ha2))
+        };
+
+        let mut header = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", algorithm={}, response=\"{}\"",
+            username, self.realm, self.nonce, uri, self.algorithm, response
+        );
+        if self.qop_auth {
+            header.push_str(&format!(", qop=auth, nc={}, cnonce=\"{}\"", count, cnonce));
+        }
+        if let Some(opaque) = &self.opaque {
+            header.push_str(&format!(", opaque=\"{}\"", opaque));
+        }
+        HeaderValue::from_str(&header).unwrap_or_else(|_| HeaderValue::from_static(""))
+    }
+}
+
+// commas inside quoted values don't separate parameters
+fn digest_params(params: &str) -> HashMap<String, String> {
+    let mut parsed = HashMap::new();
+    let mut rest = params.trim();
+    while !rest.is_empty() {
+        let (name, after) = match rest.split_once('=') {
+            Some(split) => split,
+            None => break,
+        };
+        let name = name.trim().trim_start_matches(',').trim().to_lowercase();
+        let after = after.trim_start();
+        let (value, remaining) = match after.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => (quoted, ""),
+            },
+            None => match after.find(',') {
+                Some(end) => (after[..end].trim(), &after[end..]),
+                None => (after.trim(), ""),
+            },
+        };
+        parsed.insert(name, value.to_string());
+        rest = remaining.trim_start().trim_start_matches(',').trim_start();
+    }
+    parsed
+}
+
+mod ntlm {
+    use super::*;
+
+    const SIGNATURE: &[u8; 8] = b"NTLMSSP\0";
+
+    pub struct Challenge {
+        server_challenge: [u8; 8],
+        target_info: Vec<u8>,
+    }
+
+    impl Challenge {
+        pub fn parse(token: &[u8]) -> Option<Challenge> {
+            if token.len() < 48 || &token[..8] != SIGNATURE || u32_at(token, 8)? != 2 {
+                return None;
+            }
+            let info_len = u16::from_le_bytes([token[40], token[41]]) as usize;
+            let info_offset = u32_at(token, 44)? as usize;
+            Some(Challenge {
+                server_challenge: token[24..32].try_into().ok()?,
+                target_info: token.get(info_offset..info_offset + info_len)?.to_vec(),
+            })
+        }
+    }
+
+    fn u32_at(token: &[u8], at: usize) -> Option<u32> {
+        Some(u32::from_le_bytes(token.get(at..at + 4)?.try_into().ok()?))
+    }
+
+    fn utf16(s: &str) -> Vec<u8> {
+        s.encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect()
+    }
+
+    fn hmac_md5(key: &[u8], parts: &[&[u8]]) -> Vec<u8> {
+        let mut mac = Hmac::<Md5>::new_from_slice(key).expect("hmac takes a key of any length");
+        for part in parts {
+            mac.update(part);
+        }
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    pub fn negotiate() -> Vec<u8> {
+        let mut message = SIGNATURE.to_vec();
+        message.extend_from_slice(&1u32.to_le_bytes());
+        message.extend_from_slice(&NTLM_FLAGS.to_le_bytes());
+        // no domain or workstation is supplied up front
+        message.extend_from_slice(&[0; 16]);
+        message
+    }
+
+    // an NTLMv2 response, the only kind current servers still accept
+    pub fn authenticate(credentials: &Credentials, challenge: &Challenge) -> Vec<u8> {
+        let client_challenge: [u8; 8] = rand::thread_rng().gen();
+        let since_1601 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() + FILETIME_EPOCH_OFFSET)
+            .unwrap_or_default();
+        authenticate_at(
+            credentials,
+            challenge,
+            client_challenge,
+            since_1601 * 10_000_000,
+        )
+    }
+
+    // the timestamp is in the tenths of a microsecond windows counts since 1601
+    pub(super) fn authenticate_at(
+        credentials: &Credentials,
+        challenge: &Challenge,
+        client_challenge: [u8; 8],
+        timestamp: u64,
+    ) -> Vec<u8> {
+        let (account, password) = credentials;
+        let (domain, user) = match account.split_once('\\') {
+            Some((domain, user)) => (domain, user),
+            None => ("", account.as_str()),
+        };
+        let nt_hash = Md4::digest(utf16(password.as_deref().unwrap_or_default()));
+        let key = hmac_md5(&nt_hash, &[&utf16(&(user.to_uppercase() + domain))]);
+
+        let mut blob = vec![1, 1, 0, 0, 0, 0, 0, 0];
+        blob.extend_from_slice(&timestamp.to_le_bytes());
+        blob.extend_from_slice(&client_challenge);
+        blob.extend_from_slice(&[0; 4]);
+        blob.extend_from_slice(&challenge.target_info);
+        blob.extend_from_slice(&[0; 4]);
+
+        let mut nt_response = hmac_md5(&key, &[&challenge.server_challenge, &blob]);
+        nt_response.extend_from_slice(&blob);
+        let mut lm_response = hmac_md5(&key, &[&challenge.server_challenge, &client_challenge]);
+        lm_response.extend_from_slice(&client_challenge);
+
+        let fields = [
+            lm_response,
+            nt_response,
+            utf16(domain),
+            utf16(user),
+            vec![],
+            vec![],
+        ];
+        let mut message = SIGNATURE.to_vec();
+        message.extend_from_slice(&3u32.to_le_bytes());
+        let mut offset = 64u32;
+        for field in &fields {
+            message.extend_from_slice(&(field.len() as u16).to_le_bytes());
+            message.extend_from_slice(&(field.len() as u16).to_le_bytes());
+            message.extend_from_slice(&offset.to_le_bytes());
+            offset += field.len() as u32;
+        }
+        message.extend_from_slice(&NTLM_FLAGS.to_le_bytes());
+        for field in &fields {
+            message.extend_from_slice(field);
+        }
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        s.split_whitespace()
+            .map(|byte| u8::from_str_radix(byte, 16).unwrap())
+            .collect()
+    }
+
+    fn utf16(s: &str) -> Vec<u8> {
+        s.encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect()
+    }
+
+    // the payload a security buffer at `at` in an AUTHENTICATE message points to
+    fn field(message: &[u8], at: usize) -> &[u8] {
+        let len = u16::from_le_bytes([message[at], message[at + 1]]) as usize;
+        let offset = u32::from_le_bytes(message[at + 4..at + 8].try_into().unwrap()) as usize;
+        &message[offset..offset + len]
+    }
+
+    #[test]
+    fn answers_the_rfc_2617_digest_example() {
+        let mut challenge = DigestChallenge::parse(
+            "Digest realm=\"testrealm@host.com\", qop=\"auth,auth-int\", \
+             nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", \
+             opaque=\"5ccc069c403ebaf9f0171e9517f40e41\"",
+        )
+        .unwrap();
+        let url = Url::parse("http://www.nowhere.org/dir/index.html").unwrap();
+        let credentials = ("Mufasa".to_string(), Some("Circle Of Life".to_string()));
+
+        let header = challenge.answer_with(&Method::GET, &url, &credentials, "0a4f113b");
+        let header = header.to_str().unwrap();
+
+        assert!(header.starts_with("Digest username=\"Mufasa\", realm=\"testrealm@host.com\""));
+        assert!(header.contains("uri=\"/dir/index.html\""));
+        assert!(header.contains("response=\"6629fae49393a05397450978507c4ef1\""));
+        assert!(header.contains("qop=auth, nc=00000001, cnonce=\"0a4f113b\""));
+        assert!(header.contains("opaque=\"5ccc069c403ebaf9f0171e9517f40e41\""));
+    }
+
+    #[test]
+    fn counts_each_answer_to_a_digest_challenge() {
+        let mut challenge =
+            DigestChallenge::parse("Digest realm=\"r\", nonce=\"n\", qop=auth").unwrap();
+        let url = Url::parse("http://host/").unwrap();
+        let credentials = ("user".to_string(), None);
+
+        challenge.answer_with(&Method::GET, &url, &credentials, "c");
+        let header = challenge.answer_with(&Method::GET, &url, &credentials, "c");
+
+        assert!(header.to_str().unwrap().contains("nc=00000002"));
+    }
+
+    #[test]
+    fn leaves_sha_256_digests_unanswered() {
+        assert!(
+            DigestChallenge::parse("Digest realm=\"r\", nonce=\"n\", algorithm=SHA-256").is_none()
+        );
+    }
+
+    #[test]
+    fn negotiates_with_no_domain_or_workstation() {
+        let message = ntlm::negotiate();
+
+        assert_eq!(&message[..8], b"NTLMSSP\0");
+        assert_eq!(&message[8..12], &1u32.to_le_bytes());
+        assert_eq!(&message[12..16], &NTLM_FLAGS.to_le_bytes());
+        assert_eq!(&message[16..], &[0; 16]);
+    }
+
+    // MS-NLMP 4.2.4, NTLMv2 authentication with User, Domain and Password
+    #[test]
+    fn answers_the_ms_nlmp_ntlmv2_example() {
+        let target_info = hex("02 00 0c 00 44 00 6f 00 6d 00 61 00 69 00 6e 00 \
+             01 00 0c 00 53 00 65 00 72 00 76 00 65 00 72 00 00 00 00 00");
+        let mut challenge_message = hex(
+            "4e 54 4c 4d 53 53 50 00 02 00 00 00 0c 00 0c 00 38 00 00 00 33 82 8a e2 \
+             01 23 45 67 89 ab cd ef 00 00 00 00 00 00 00 00 24 00 24 00 44 00 00 00 \
+             06 00 70 17 00 00 00 0f 53 00 65 00 72 00 76 00 65 00 72 00",
+        );
+        challenge_message.extend_from_slice(&target_info);
+        let challenge = ntlm::Challenge::parse(&challenge_message).unwrap();
+        let credentials = ("Domain\\User".to_string(), Some("Password".to_string()));
+
+        let message = ntlm::authenticate_at(&credentials, &challenge, [0xaa; 8], 0);
+
+        assert_eq!(&message[..8], b"NTLMSSP\0");
+        assert_eq!(&message[8..12], &3u32.to_le_bytes());
+        let mut lm_response = hex("86 c3 50 97 ac 9c ec 10 25 54 76 4a 57 cc cc 19");
+        lm_response.extend_from_slice(&[0xaa; 8]);
+        assert_eq!(field(&message, 12), lm_response.as_slice());
+        let nt_response = field(&message, 20);
+        assert_eq!(
+            &nt_response[..16],
+            hex("68 cd 0a b8 51 e5 1c 96 aa bc 92 7b eb ef 6a 1c").as_slice()
+        );
+        let mut blob = hex("01 01 00 00 00 00 00 00 00 00 00 00 00 00 00 00");
+        blob.extend_from_slice(&[0xaa; 8]);
+        blob.extend_from_slice(&[0; 4]);
+        blob.extend_from_slice(&target_info);
+        blob.extend_from_slice(&[0; 4]);
+        assert_eq!(&nt_response[16..], blob.as_slice());
+        assert_eq!(field(&message, 28), utf16("Domain").as_slice());
+        assert_eq!(field(&message, 36), utf16("User").as_slice());
+        assert_eq!(&message[60..64], &NTLM_FLAGS.to_le_bytes());
+    }
+}
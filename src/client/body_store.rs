@@ -0,0 +1,68 @@
+use chrono::Local;
+use serde_json::json;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::warn;
+use uuid::Uuid;
+
+const MANIFEST: &str = "manifest.jsonl";
+
+pub struct BodyStore {
+    dir: PathBuf,
+    manifest: Mutex<LineWriter<File>>,
+    skip_content_types: Vec<String>,
+}
+
+impl BodyStore {
+    /// Binary content types are left out unless `save_all` is set.
+    pub fn create(
+        dir: &str,
+        save_all: bool,
+        skip_content_types: &[String],
+    ) -> io::Result<BodyStore> {
+        fs::create_dir_all(dir)?;
+        let manifest = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(PathBuf::from(dir).join(MANIFEST))?;
+        Ok(BodyStore {
+            dir: PathBuf::from(dir),
+            manifest: Mutex::new(LineWriter::new(manifest)),
+            skip_content_types: match save_all {
+                true => vec![],
+                false => skip_content_types.to_vec(),
+            },
+        })
+    }
+
+    pub fn wants(&self, content_type: &str) -> bool {
+        let content_type = content_type.to_lowercase();
+        !self
+            .skip_content_types
+            .iter()
+            .any(|skip| content_type.starts_with(skip.as_str()))
+    }
+
+    // the file name is a hash of the URL, so fetching a page again overwrites its last body
+    pub fn save(&self, url: &str, status: u16, content_type: &str, body: &[u8]) {
+        let file = format!(
+            "{}.body",
+            Uuid::new_v5(&Uuid::NAMESPACE_URL, url.as_bytes())
+        );
+        if let Err(why) = fs::write(self.dir.join(&file), body) {
+            warn!("Couldn't save the body of {}: {}", url, why);
+            return;
+        }
+        let entry = json!({
+            "file": file,
+            "url": url,
+            "status": status,
+            "content_type": content_type,
+            "bytes": body.len(),
+            "timestamp": Local::now().to_rfc3339(),
+        });
+        let _ = writeln!(self.manifest.lock().unwrap(), "{}", entry);
+    }
+}
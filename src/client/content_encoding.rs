@@ -0,0 +1,39 @@
+use brotli_decompressor::Decompressor;
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use std::io::Read;
+use tracing::debug;
+
+pub const ACCEPTED: &str = "gzip, deflate, br";
+
+/// Decodes a body sent with one of the encodings in `ACCEPTED`, stopping at `limit` decoded
+/// bytes so a small compressed body can't expand without bound. A body cut short by a partial
+/// GET decodes as far as it goes.
+pub fn decode(encoding: &str, body: Vec<u8>, limit: usize) -> Vec<u8> {
+    let limit = match limit {
+        0 => u64::MAX,
+        limit => limit as u64,
+    };
+    let mut decoded = vec![];
+    let result = match encoding.trim().to_lowercase().as_str() {
+        "gzip" | "x-gzip" => GzDecoder::new(body.as_slice())
+            .take(limit)
+            .read_to_end(&mut decoded),
+        // deflate is meant to be zlib wrapped, but plenty of servers send it raw
+        "deflate" if body.first().is_some_and(|b| b & 0x0f == 8) => {
+            ZlibDecoder::new(body.as_slice())
+                .take(limit)
+                .read_to_end(&mut decoded)
+        }
+        "deflate" => DeflateDecoder::new(body.as_slice())
+            .take(limit)
+            .read_to_end(&mut decoded),
+        "br" => Decompressor::new(body.as_slice(), 4096)
+            .take(limit)
+            .read_to_end(&mut decoded),
+        _ => return body,
+    };
+    if let Err(why) = result {
+        debug!("{} body only partly decoded: {}", encoding, why);
+    }
+    decoded
+}
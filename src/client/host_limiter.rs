@@ -0,0 +1,66 @@
+use reqwest::Url;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{self, Instant};
+
+pub struct HostLimiter {
+    max_per_host: usize,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+    delay: Duration,
+    next_allowed: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostLimiter {
+    pub fn new(max_per_host: usize, delay_ms: u64) -> HostLimiter {
+        HostLimiter {
+            max_per_host,
+            hosts: Mutex::new(HashMap::new()),
+            delay: Duration::from_millis(delay_ms),
+            next_allowed: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Waits until the delay has passed since the last request to the URL's host was let
+    /// through. Each caller takes the next free slot before sleeping, so requests waiting
+    /// at the same time are spaced out rather than released together.
+    pub async fn space_out(&self, url: &str) {
+        if self.delay.is_zero() {
+            return;
+        }
+        let host = match Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(String::from))
+        {
+            Some(host) => host,
+            None => return,
+        };
+        let slot = {
+            let mut next_allowed = self.next_allowed.lock().unwrap();
+            let now = Instant::now();
+            let slot = next_allowed.get(&host).map_or(now, |next| (*next).max(now));
+            next_allowed.insert(host, slot + self.delay);
+            slot
+        };
+        time::sleep_until(slot).await;
+    }
+
+    /// Waits until a request to the URL's host is allowed. No limit is applied when
+    /// max_per_host is 0 or the URL has no host.
+    pub async fn acquire(&self, url: &str) -> Option<OwnedSemaphorePermit> {
+        if self.max_per_host == 0 {
+            return None;
+        }
+        let host = Url::parse(url).ok()?.host_str()?.to_string();
+        let semaphore = {
+            let mut hosts = self.hosts.lock().unwrap();
+            Arc::clone(
+                hosts
+                    .entry(host)
+                    .or_insert_with(|| Arc::new(Semaphore::new(self.max_per_host))),
+            )
+        };
+        semaphore.acquire_owned().await.ok()
+    }
+}
@@ -0,0 +1,74 @@
+use chrono::Local;
+use reqwest::header::HeaderMap;
+use reqwest::{Request, Response, Result};
+use serde_json::{json, Map, Value};
+use std::fs::{File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub struct HttpTrace {
+    file: Mutex<LineWriter<File>>,
+    default_headers: HeaderMap,
+}
+
+impl HttpTrace {
+    pub fn create(path: &str, default_headers: HeaderMap) -> io::Result<HttpTrace> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(HttpTrace {
+            file: Mutex::new(LineWriter::new(file)),
+            default_headers,
+        })
+    }
+
+    // the client adds its default headers as the request is sent, so they're merged in here to
+    // show everything that went over the wire
+    pub fn describe(&self, request: &Request) -> Value {
+        let mut headers = self.default_headers.clone();
+        for (name, value) in request.headers() {
+            headers.insert(name, value.clone());
+        }
+        json!({
+            "method": request.method().as_str(),
+            "url": request.url().as_str(),
+            "headers": HttpTrace::headers_to_json(&headers),
+        })
+    }
+
+    pub fn record(&self, request: Value, result: &Result<Response>, elapsed: Duration) {
+        let response = match result {
+            Ok(res) => json!({
+                "status": res.status().as_u16(),
+                "headers": HttpTrace::headers_to_json(res.headers()),
+            }),
+            Err(why) => json!({ "error": why.to_string() }),
+        };
+        let entry = json!({
+            "timestamp": Local::now().to_rfc3339(),
+            "elapsed_ms": elapsed.as_millis() as u64,
+            "request": request,
+            "response": response,
+        });
+        let _ = writeln!(self.file.lock().unwrap(), "{}", entry);
+    }
+
+    fn headers_to_json(headers: &HeaderMap) -> Value {
+        let mut map = Map::new();
+        for (name, value) in headers {
+            // credentials taken from a target url stay out of the trace
+            let value = if value.is_sensitive() {
+                Value::String("<redacted>".to_string())
+            } else {
+                Value::String(String::from_utf8_lossy(value.as_bytes()).into_owned())
+            };
+            match map.get_mut(name.as_str()) {
+                Some(Value::Array(values)) => values.push(value),
+                Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+                None => {
+                    map.insert(name.to_string(), value);
+                }
+            }
+        }
+        Value::Object(map)
+    }
+}
@@ -1,88 +1,733 @@
-use crate::config::RinzlerSettings;
+pub(crate) mod auth;
+pub(crate) mod body_store;
+pub(crate) mod content_encoding;
+pub(crate) mod host_limiter;
+pub(crate) mod http_trace;
+pub(crate) mod replay;
+pub(crate) mod throttle;
+pub(crate) mod tls_info;
+pub(crate) mod user_agents;
+
+use crate::client::auth::{Answer, Authenticator, Credentials};
+use crate::client::body_store::BodyStore;
+use crate::client::host_limiter::HostLimiter;
+use crate::client::http_trace::HttpTrace;
+use crate::client::throttle::{retry_after, Throttle};
+use crate::config::{AuthType, HttpVersion, RinzlerSettings, FUZZ_KEYWORD};
+use crate::crawler::crawl_progress::CrawlProgress;
 use crate::crawler::crawl_target::CrawlTarget;
-use reqwest::blocking::{Client, Response};
-use reqwest::header::HeaderMap;
-use reqwest::{Method, Result};
-use std::sync::Arc;
+use crate::crawler::response_cache::CacheEntry;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE, AUTHORIZATION,
+    CONTENT_ENCODING, CONTENT_TYPE, HOST, IF_MODIFIED_SINCE, IF_NONE_MATCH, LOCATION, RANGE,
+    USER_AGENT,
+};
+use reqwest::{Client, Method, RequestBuilder, Response, Result, StatusCode, Url};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::process;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::time::{self, Instant};
+use tracing::{debug, error, warn};
+
+const MAX_RETRIES: usize = 3;
+const MAX_REDIRECTS: usize = 10;
+// how many NTLM handshakes are tried before a 401 is given back
+const NTLM_HANDSHAKES: usize = 2;
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+pub(crate) const TRACE_MARKER: &str = "x-rinzler-trace";
 
 pub struct RequestOptions {
     truncate: bool,
+    follow_redirects: bool,
 }
 impl RequestOptions {
     pub const fn default() -> Option<RequestOptions> {
-        Some(RequestOptions { truncate: false })
+        Some(RequestOptions {
+            truncate: false,
+            follow_redirects: true,
+        })
     }
 
     pub const fn with_partial_get() -> Option<RequestOptions> {
-        Some(RequestOptions { truncate: true })
+        Some(RequestOptions {
+            truncate: true,
+            follow_redirects: true,
+        })
+    }
+
+    pub const fn without_redirects() -> Option<RequestOptions> {
+        Some(RequestOptions {
+            truncate: false,
+            follow_redirects: false,
+        })
+    }
+
+    fn follows_redirects(opt: &Option<RequestOptions>) -> bool {
+        opt.as_ref().is_none_or(|o| o.follow_redirects)
     }
 }
 
 pub struct RinzlerClient {
     client: Arc<Client>,
+    throttle: Arc<Throttle>,
+    host_limiter: Arc<HostLimiter>,
+    in_flight: Arc<Semaphore>,
+    progress: Arc<CrawlProgress>,
+    preview_bytes: u64,
+    max_body_size: usize,
+    agent_rng: Option<Arc<Mutex<StdRng>>>,
+    agents_by_host: Arc<HashMap<String, String>>,
+    // targets read from stdin can bring credentials of their own once the scan has started
+    credentials_by_origin: Arc<RwLock<HashMap<String, Credentials>>>,
+    authenticator: Arc<Authenticator>,
+    host_header: Option<String>,
+    // the targets' origins, the only ones --host-header is sent to
+    host_header_origins: Arc<RwLock<HashSet<String>>>,
+    trace: Option<Arc<HttpTrace>>,
+    bodies: Option<Arc<BodyStore>>,
+    // sent only with --data's body, so a redirect that drops the body drops them too
+    body_headers: HeaderMap,
 }
 
 impl Clone for RinzlerClient {
     fn clone(&self) -> Self {
         RinzlerClient {
             client: Arc::clone(&self.client),
+            throttle: Arc::clone(&self.throttle),
+            host_limiter: Arc::clone(&self.host_limiter),
+            in_flight: Arc::clone(&self.in_flight),
+            progress: Arc::clone(&self.progress),
+            preview_bytes: self.preview_bytes,
+            max_body_size: self.max_body_size,
+            agent_rng: self.agent_rng.clone(),
+            agents_by_host: Arc::clone(&self.agents_by_host),
+            credentials_by_origin: Arc::clone(&self.credentials_by_origin),
+            authenticator: Arc::clone(&self.authenticator),
+            host_header: self.host_header.clone(),
+            host_header_origins: Arc::clone(&self.host_header_origins),
+            trace: self.trace.clone(),
+            bodies: self.bodies.clone(),
+            body_headers: self.body_headers.clone(),
         }
     }
 }
 
 impl RinzlerClient {
-    pub(crate) fn new(settings: &RinzlerSettings) -> RinzlerClient {
+    pub(crate) fn new(
+        settings: &RinzlerSettings,
+        throttle: Arc<Throttle>,
+        host_limiter: Arc<HostLimiter>,
+        progress: Arc<CrawlProgress>,
+    ) -> RinzlerClient {
         RinzlerClient {
             client: RinzlerClient::create_http_client(settings),
+            throttle,
+            host_limiter,
+            in_flight: Arc::new(Semaphore::new(RinzlerClient::max_connections(settings))),
+            progress,
+            preview_bytes: settings.preview_bytes,
+            max_body_size: settings.max_body_size,
+            agent_rng: settings.random_agent_each.then(|| {
+                Arc::new(Mutex::new(match settings.seed {
+                    Some(seed) => StdRng::seed_from_u64(seed),
+                    None => StdRng::from_entropy(),
+                }))
+            }),
+            agents_by_host: Arc::new(settings.user_agents_by_host.clone()),
+            credentials_by_origin: Arc::new(RwLock::new(settings.credentials_by_origin.clone())),
+            authenticator: Arc::new(Authenticator::new(
+                settings.auth_type,
+                (settings.auth_type == AuthType::Ntlm)
+                    .then(|| RinzlerClient::create_ntlm_client(settings)),
+            )),
+            host_header: settings.host_header.clone(),
+            host_header_origins: Arc::new(RwLock::new(
+                settings
+                    .hosts
+                    .iter()
+                    .filter_map(|host| Some(Url::parse(host).ok()?.origin().ascii_serialization()))
+                    .collect(),
+            )),
+            trace: settings.trace_http.as_deref().map(|path| {
+                let mut headers = RinzlerClient::default_headers(settings);
+                if let Ok(user_agent) = HeaderValue::from_str(&settings.user_agent) {
+                    headers.insert(USER_AGENT, user_agent);
+                }
+                Arc::new(HttpTrace::create(path, headers).unwrap_or_else(|why| {
+                    error!("HTTP trace '{}' couldn't be opened: {}", path, why);
+                    process::exit(1);
+                }))
+            }),
+            bodies: settings.save_bodies.as_deref().map(|dir| {
+                let store = BodyStore::create(dir, settings.save_all, &settings.skip_content_types);
+                Arc::new(store.unwrap_or_else(|why| {
+                    error!("bodies can't be saved to '{}': {}", dir, why);
+                    process::exit(1);
+                }))
+            }),
+            body_headers: RinzlerClient::body_headers(settings),
         }
     }
 
-    pub(crate) fn send_get(
+    pub(crate) async fn send_get(
         &self,
         ct: &mut CrawlTarget,
         opt: Option<RequestOptions>,
     ) -> Result<Response> {
-        ct.method = Method::GET.to_string();
+        self.send(ct, Method::GET, None, HeaderMap::new(), opt)
+            .await
+    }
 
-        let result = self.client.get(&ct.url).send();
-        result
+    pub(crate) async fn send_head(
+        &self,
+        ct: &mut CrawlTarget,
+        opt: Option<RequestOptions>,
+    ) -> Result<Response> {
+        self.send(ct, Method::HEAD, None, HeaderMap::new(), opt)
+            .await
     }
 
-    pub(crate) fn send_head(
+    pub(crate) async fn send(
         &self,
         ct: &mut CrawlTarget,
+        method: Method,
+        body: Option<String>,
+        headers: HeaderMap,
         opt: Option<RequestOptions>,
     ) -> Result<Response> {
-        ct.method = Method::HEAD.to_string();
-        let result = self.client.head(&ct.url).send();
-        result
+        ct.method = method.to_string();
+        let follow = RequestOptions::follows_redirects(&opt);
+        let truncate =
+            method == Method::GET && opt.is_some_and(|o| o.truncate) && self.preview_bytes > 0;
+        self.send_following_redirects(ct, method.clone(), follow, |url, hop_method| {
+            let mut request = self
+                .request(hop_method.clone(), url)
+                .headers(headers.clone());
+            if truncate {
+                request = request.header(RANGE, format!("bytes=0-{}", self.preview_bytes - 1));
+            }
+            // a redirect that turned the request into a GET leaves the body behind
+            if let Some(body) = body.as_ref().filter(|_| *hop_method == method) {
+                request = request
+                    .headers(self.body_headers.clone())
+                    .body(body.clone());
+            }
+            request
+        })
+        .await
+    }
+
+    pub(crate) async fn send_conditional_get(
+        &self,
+        ct: &mut CrawlTarget,
+        cached: &CacheEntry,
+    ) -> Result<Response> {
+        ct.method = Method::GET.to_string();
+        self.send_following_redirects(ct, Method::GET, true, |url, _| {
+            let mut request = self.request(Method::GET, url);
+            if self.preview_bytes > 0 {
+                request = request.header(RANGE, format!("bytes=0-{}", self.preview_bytes - 1));
+            }
+            if let Some(etag) = &cached.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+            request
+        })
+        .await
     }
 
-    pub(crate) fn send_options(
+    pub(crate) async fn send_options(
         &self,
         crawl_target: &mut CrawlTarget,
         opt: Option<RequestOptions>,
     ) -> Result<Response> {
         crawl_target.method = Method::OPTIONS.to_string();
+        let follow = RequestOptions::follows_redirects(&opt);
+        self.send_following_redirects(crawl_target, Method::OPTIONS, follow, |url, method| {
+            self.request(method.clone(), url)
+        })
+        .await
+    }
+
+    // TRACE, or IIS's TRACK, carrying a marker to look for in the request the server echoes back
+    pub(crate) async fn send_trace(
+        &self,
+        crawl_target: &mut CrawlTarget,
+        method: Method,
+        marker: &str,
+    ) -> Result<Response> {
+        crawl_target.method = method.to_string();
+        self.send_following_redirects(crawl_target, method, false, |url, method| {
+            self.request(method.clone(), url)
+                .header(TRACE_MARKER, marker)
+        })
+        .await
+    }
+
+    pub(crate) async fn read_body(&self, res: Response) -> Result<Vec<u8>> {
+        self.read_body_reporting(res, |_| async {}).await
+    }
+
+    // stops reading once the cap is reached, so a huge or endless body can't exhaust memory.
+    // `on_chunk` is told how many bytes have been read so far each time more arrive
+    pub(crate) async fn read_body_reporting<F, R>(
+        &self,
+        mut res: Response,
+        mut on_chunk: F,
+    ) -> Result<Vec<u8>>
+    where
+        F: FnMut(usize) -> R,
+        R: Future<Output = ()>,
+    {
+        let header = |name| {
+            res.headers()
+                .get(name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let content_type = header(CONTENT_TYPE);
+        let content_encoding = header(CONTENT_ENCODING);
+        let mut body = vec![];
+        let url = res.url().to_string();
+        // a body that stalls part way through is as much a failed request as one that never starts
+        while let Some(chunk) = res
+            .chunk()
+            .await
+            .inspect_err(|why| self.progress.failed(&url, why))?
+        {
+            body.extend_from_slice(&chunk);
+            on_chunk(body.len()).await;
+            if self.max_body_size > 0 && body.len() >= self.max_body_size {
+                debug!(
+                    "{} is larger than {} bytes, ignoring the rest",
+                    res.url(),
+                    self.max_body_size
+                );
+                body.truncate(self.max_body_size);
+                break;
+            }
+        }
+        let body = content_encoding::decode(&content_encoding, body, self.max_body_size);
+        if let Some(bodies) = self.bodies.as_ref().filter(|b| b.wants(&content_type)) {
+            bodies.save(
+                res.url().as_str(),
+                res.status().as_u16(),
+                &content_type,
+                &body,
+            );
+        }
+        Ok(body)
+    }
+
+    // a user-agent set for the host wins over a random one
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let mut request = self.client.request(method.clone(), url);
+        if let Some(credentials) = self.credentials_for(url) {
+            request = self
+                .authenticator
+                .authorize(request, &method, url, &credentials);
+        }
+        if let Some(host) = self.host_header_for(url) {
+            request = request.header(HOST, host);
+        }
+        if let Some(user_agent) = self.user_agent_for(url) {
+            return request.header(USER_AGENT, user_agent);
+        }
+        match &self.agent_rng {
+            Some(rng) => request.header(USER_AGENT, user_agents::random(&mut *rng.lock().unwrap())),
+            None => request,
+        }
+    }
+
+    fn user_agent_for(&self, url: &str) -> Option<&str> {
+        if self.agents_by_host.is_empty() {
+            return None;
+        }
+        let url = Url::parse(url).ok()?;
+        self.agents_by_host
+            .get(url.host_str()?)
+            .map(|user_agent| user_agent.as_str())
+    }
+
+    // a redirect to another host, or a link off to one, is sent that host's own name
+    fn host_header_for(&self, url: &str) -> Option<&str> {
+        let host = self.host_header.as_deref()?;
+        let url = Url::parse(url).ok()?;
+        self.host_header_origins
+            .read()
+            .unwrap()
+            .contains(&url.origin().ascii_serialization())
+            .then_some(host)
+    }
+
+    /// Sends --host-header to a target read once the scan has started, as to those given up front.
+    pub(crate) fn add_target(&self, target: &str) {
+        if let Ok(url) = Url::parse(target) {
+            self.host_header_origins
+                .write()
+                .unwrap()
+                .insert(url.origin().ascii_serialization());
+        }
+    }
+
+    // only the origin the credentials were given for gets them, wherever a redirect leads
+    fn credentials_for(&self, url: &str) -> Option<Credentials> {
+        let credentials_by_origin = self.credentials_by_origin.read().unwrap();
+        if credentials_by_origin.is_empty() {
+            return None;
+        }
+        let url = Url::parse(url).ok()?;
+        credentials_by_origin
+            .get(&url.origin().ascii_serialization())
+            .cloned()
+    }
+
+    pub(crate) fn add_credentials(&self, origin: String, credentials: Credentials) {
+        self.credentials_by_origin
+            .write()
+            .unwrap()
+            .insert(origin, credentials);
+    }
+
+    // redirects are followed here rather than by reqwest so every hop is recorded on the target
+    async fn send_following_redirects<F>(
+        &self,
+        ct: &mut CrawlTarget,
+        mut method: Method,
+        follow: bool,
+        build_request: F,
+    ) -> Result<Response>
+    where
+        F: Fn(&str, &Method) -> RequestBuilder,
+    {
+        ct.redirect_chain.clear();
+        let mut url = ct.url.clone();
+        loop {
+            let hop = |url: &str| build_request(url, &method);
+            let (res, elapsed) = self.send_with_retry(&url, &hop).await?;
+            ct.response_time_ms = Some(elapsed.as_millis() as u64);
+            ct.content_encoding = res
+                .headers()
+                .get(CONTENT_ENCODING)
+                .and_then(|encoding| encoding.to_str().ok())
+                .map(|encoding| encoding.to_string());
+            match RinzlerClient::redirect_location(&res) {
+                // going round a loop again wouldn't tell us anything, the caller reports it instead
+                Some(next) if RinzlerClient::redirect_loop(ct, &res).is_some() => {
+                    debug!("{} redirects back to {}", url, next);
+                    return Ok(res);
+                }
+                Some(next) if follow && ct.redirect_chain.len() < MAX_REDIRECTS => {
+                    ct.redirect_chain.push((res.status().as_u16(), url));
+                    method = RinzlerClient::redirected_method(method, res.status());
+                    url = next.to_string();
+                }
+                _ => return Ok(res),
+            }
+        }
+    }
+
+    // like a browser, only a 307 or 308 repeats a POST, and a 303 asks for a GET whatever was sent
+    fn redirected_method(method: Method, status: StatusCode) -> Method {
+        match status {
+            StatusCode::SEE_OTHER if method != Method::HEAD => Method::GET,
+            StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND if method == Method::POST => {
+                Method::GET
+            }
+            _ => method,
+        }
+    }
+
+    pub(crate) fn redirect_location(res: &Response) -> Option<Url> {
+        if !res.status().is_redirection() {
+            return None;
+        }
+        let location = res.headers().get(LOCATION)?.to_str().ok()?;
+        res.url().join(location).ok()
+    }
+
+    /// The URL a redirect goes back to, when it's one already visited on the way to `res`.
+    pub(crate) fn redirect_loop(ct: &CrawlTarget, res: &Response) -> Option<Url> {
+        let next = RinzlerClient::redirect_location(res)?;
+        let looped = next == *res.url()
+            || ct
+                .redirect_chain
+                .iter()
+                .any(|(_, hop)| Url::parse(hop).is_ok_and(|hop| hop == next));
+        looped.then_some(next)
+    }
+
+    // the elapsed time is for the last attempt only, from sending the request to its headers
+    async fn send_with_retry<F>(&self, url: &str, build_request: &F) -> Result<(Response, Duration)>
+    where
+        F: Fn(&str) -> RequestBuilder,
+    {
+        let mut attempt = 0;
+        loop {
+            let (res, elapsed) = self.send_authenticated(url, build_request).await?;
+            self.throttle.succeeded();
+            self.progress.responded(res.url(), elapsed);
+            if !Throttle::is_overloaded(&res) {
+                return Ok((res, elapsed));
+            }
 
-        let result = self
-            .client
-            .request(Method::OPTIONS, &crawl_target.url)
-            .send();
-        result
+            self.throttle.back_off();
+            match retry_after(&res) {
+                Some(wait) if attempt < MAX_RETRIES => {
+                    debug!(
+                        "{} returned {}, retrying in {}s",
+                        res.url(),
+                        res.status(),
+                        wait.as_secs()
+                    );
+                    time::sleep(wait).await;
+                    attempt += 1;
+                }
+                _ => return Ok((res, elapsed)),
+            }
+        }
+    }
+
+    // a 401 from an origin there are credentials for is answered here, and every request sent to
+    // answer it waits its turn and is counted like any other
+    async fn send_authenticated<F>(
+        &self,
+        url: &str,
+        build_request: &F,
+    ) -> Result<(Response, Duration)>
+    where
+        F: Fn(&str) -> RequestBuilder,
+    {
+        let credentials = self.credentials_for(url);
+        let connection = credentials
+            .as_ref()
+            .and_then(|_| self.authenticator.connection(url));
+        let (client, _turn) = match &connection {
+            Some((client, turn)) => (*client, Some(turn.lock().await)),
+            None => (&*self.client, None),
+        };
+        let (res, elapsed) = self.send_once(client, url, build_request(url)).await?;
+        let credentials = match credentials {
+            Some(credentials) if res.status() == StatusCode::UNAUTHORIZED => credentials,
+            _ => return Ok((res, elapsed)),
+        };
+        match self.authenticator.answer(&res) {
+            Answer::Resend => {
+                let _ = res.bytes().await;
+                self.send_once(client, url, build_request(url)).await
+            }
+            Answer::Handshake(scheme) => {
+                self.handshake(client, url, &credentials, scheme, res, build_request)
+                    .await
+            }
+            Answer::GiveUp => Ok((res, elapsed)),
+        }
+    }
+
+    // the pool can hand the last request of a handshake a connection other than the one it
+    // negotiated on, so a handshake that doesn't take is tried once more
+    async fn handshake<F>(
+        &self,
+        client: &Client,
+        url: &str,
+        credentials: &Credentials,
+        scheme: &str,
+        challenged: Response,
+        build_request: &F,
+    ) -> Result<(Response, Duration)>
+    where
+        F: Fn(&str) -> RequestBuilder,
+    {
+        let mut res = challenged;
+        let mut elapsed = Duration::ZERO;
+        for _ in 0..NTLM_HANDSHAKES {
+            // reading the body lets the connection go back to the pool for the handshake to use
+            let _ = res.bytes().await;
+            let negotiate =
+                build_request(url).header(AUTHORIZATION, Authenticator::negotiate(scheme));
+            let (negotiated, negotiate_elapsed) = self.send_once(client, url, negotiate).await?;
+            let authenticate = match Authenticator::authenticate(scheme, credentials, &negotiated) {
+                Some(authenticate) => authenticate,
+                None => return Ok((negotiated, negotiate_elapsed)),
+            };
+            let _ = negotiated.bytes().await;
+            let authenticate = build_request(url).header(AUTHORIZATION, authenticate);
+            (res, elapsed) = self.send_once(client, url, authenticate).await?;
+            if res.status() != StatusCode::UNAUTHORIZED {
+                break;
+            }
+        }
+        Ok((res, elapsed))
+    }
+
+    // the elapsed time is from sending the request to its headers
+    async fn send_once(
+        &self,
+        client: &Client,
+        url: &str,
+        request: RequestBuilder,
+    ) -> Result<(Response, Duration)> {
+        self.throttle.wait().await;
+        let _host_permit = self.host_limiter.acquire(url).await;
+        // waiting for the host comes before taking one of the slots shared by every host
+        self.host_limiter.space_out(url).await;
+        let _permit = self.in_flight.acquire().await;
+        self.progress.requested();
+        let request = request.build()?;
+        let traced = self.trace.as_ref().map(|trace| trace.describe(&request));
+        let started = Instant::now();
+        let res = client.execute(request).await;
+        let elapsed = started.elapsed();
+        if let (Some(trace), Some(traced)) = (&self.trace, traced) {
+            trace.record(traced, &res, elapsed);
+        }
+        let res = res.inspect_err(|why| {
+            self.progress.failed(url, why);
+            self.throttle.failed();
+        })?;
+        Ok((res, elapsed))
+    }
+
+    pub(crate) fn header_map<I>(headers: I) -> HeaderMap
+    where
+        I: Iterator<Item = (String, String)>,
+    {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            match (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                (Ok(name), Ok(value)) => {
+                    map.append(name, value);
+                }
+                _ => warn!("Skipping invalid header '{}: {}'", name, value),
+            }
+        }
+        map
+    }
+
+    // headers carrying the fuzz keyword are filled in per request instead
+    fn body_headers(settings: &RinzlerSettings) -> HeaderMap {
+        RinzlerClient::header_map(
+            settings
+                .headers
+                .iter()
+                .filter(|(name, value)| {
+                    !name.contains(FUZZ_KEYWORD) && !value.contains(FUZZ_KEYWORD)
+                })
+                .filter(|(name, _)| RinzlerClient::goes_with_body(settings, name))
+                .map(|(name, value)| (name.clone(), value.clone())),
+        )
+    }
+
+    fn goes_with_body(settings: &RinzlerSettings, name: &str) -> bool {
+        settings.data.is_some() && name.eq_ignore_ascii_case(CONTENT_TYPE.as_str())
+    }
+
+    fn default_headers(settings: &RinzlerSettings) -> HeaderMap {
+        // a header given with -H wins over the shortcut for it
+        let given = |header: &HeaderName| {
+            settings
+                .headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case(header.as_str()))
+        };
+        // reqwest isn't left to decompress bodies itself, as it hides the Content-Encoding they had
+        let accept_encoding = settings.compression && !given(&ACCEPT_ENCODING);
+        let shortcuts = [
+            (ACCEPT, &settings.accept),
+            (ACCEPT_LANGUAGE, &settings.accept_language),
+        ];
+        RinzlerClient::header_map(
+            settings
+                .headers
+                .iter()
+                .filter(|(name, value)| {
+                    !name.contains(FUZZ_KEYWORD) && !value.contains(FUZZ_KEYWORD)
+                })
+                .filter(|(name, _)| !RinzlerClient::goes_with_body(settings, name))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .chain(
+                    shortcuts
+                        .into_iter()
+                        .filter(|(name, _)| !given(name))
+                        .filter_map(|(name, value)| Some((name.to_string(), value.clone()?))),
+                )
+                .chain(accept_encoding.then(|| {
+                    (
+                        ACCEPT_ENCODING.to_string(),
+                        content_encoding::ACCEPTED.to_string(),
+                    )
+                })),
+        )
+    }
+
+    // more connections than threads would never all be busy, so threads still bound them
+    fn max_connections(settings: &RinzlerSettings) -> usize {
+        match settings.max_connections {
+            Some(max_connections) => max_connections.min(settings.max_threads),
+            None => settings.max_threads,
+        }
+    }
+
+    fn max_connections_per_host(settings: &RinzlerSettings) -> usize {
+        match settings.max_per_host {
+            0 => RinzlerClient::max_connections(settings),
+            max_per_host => max_per_host.min(RinzlerClient::max_connections(settings)),
+        }
     }
 
     fn create_http_client(settings: &RinzlerSettings) -> Arc<Client> {
-        let headers = HeaderMap::new();
-        let client = reqwest::blocking::ClientBuilder::new()
+        let builder = RinzlerClient::http_client_builder(settings)
+            // no more connections sit idle than could ever be busy at once, so sockets get reused
+            // rather than piling up in TIME_WAIT on long scans
+            .pool_max_idle_per_host(RinzlerClient::max_connections_per_host(settings));
+        let builder = match settings.http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1 => builder.http1_only(),
+            HttpVersion::Http2 => builder.http2_prior_knowledge(),
+        };
+        Arc::new(builder.build().unwrap())
+    }
+
+    // one connection per host, which the handshake authenticates; NTLM doesn't work over HTTP/2
+    fn create_ntlm_client(settings: &RinzlerSettings) -> Client {
+        RinzlerClient::http_client_builder(settings)
+            .pool_max_idle_per_host(1)
+            .http1_only()
+            .build()
+            .unwrap()
+    }
+
+    fn http_client_builder(settings: &RinzlerSettings) -> reqwest::ClientBuilder {
+        let headers = RinzlerClient::default_headers(settings);
+        let mut builder = reqwest::ClientBuilder::new()
             .user_agent(settings.user_agent.as_str())
             .danger_accept_invalid_certs(true)
             .default_headers(headers)
-            //.timeout(Duration::from_millis(5000))
-            .redirect(reqwest::redirect::Policy::limited(10))
-            .build()
-            .unwrap();
-
-        Arc::new(client)
+            .redirect(reqwest::redirect::Policy::none())
+            .tcp_keepalive(TCP_KEEPALIVE);
+        if let Some(timeout) = settings.timeout {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+        if let Some(connect_timeout) = settings.connect_timeout {
+            builder = builder.connect_timeout(Duration::from_secs(connect_timeout));
+        }
+        // port 0 keeps whichever port the URL asks for
+        for (host, ip) in &settings.resolve {
+            builder = builder.resolve(host, SocketAddr::new(*ip, 0));
+        }
+        builder
     }
 }
@@ -0,0 +1,181 @@
+use crate::app::RinzlerApplication;
+use crate::client::host_limiter::HostLimiter;
+use crate::client::{RequestOptions, RinzlerClient};
+use crate::config::{DiffFormat, RinzlerSettings};
+use crate::crawler::crawl_target::CrawlTarget;
+use colored::Colorize;
+use futures::{stream, StreamExt};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_LENGTH, HOST};
+use reqwest::Method;
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::sync::Arc;
+
+pub struct RecordedRequest {
+    method: Method,
+    url: String,
+    headers: HeaderMap,
+    status: Option<u16>,
+}
+
+pub struct Replay {
+    requests: Vec<(RecordedRequest, Option<u16>)>,
+}
+
+impl RecordedRequest {
+    pub fn read(path: &str) -> io::Result<Vec<RecordedRequest>> {
+        let mut requests = vec![];
+        for (number, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: Value = serde_json::from_str(&line).map_err(|why| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {}", number + 1, why),
+                )
+            })?;
+            let request = RecordedRequest::from_entry(&entry).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {} isn't a --trace-http entry", number + 1),
+                )
+            })?;
+            requests.push(request);
+        }
+        Ok(requests)
+    }
+
+    fn from_entry(entry: &Value) -> Option<RecordedRequest> {
+        let request = entry.get("request")?;
+        let method = request.get("method")?.as_str()?.parse().ok()?;
+        let url = request.get("url")?.as_str()?.to_string();
+        let mut headers = HeaderMap::new();
+        for (name, values) in request.get("headers")?.as_object()? {
+            let name: HeaderName = name.parse().ok()?;
+            // the client works these out again for whatever it sends
+            if name == CONTENT_LENGTH || name == HOST {
+                continue;
+            }
+            let values = match values {
+                Value::Array(values) => values.iter().collect(),
+                value => vec![value],
+            };
+            for value in values.into_iter().filter_map(Value::as_str) {
+                // redacted credentials were never written down, so there's nothing to send again
+                if value == "<redacted>" {
+                    continue;
+                }
+                headers.append(&name, HeaderValue::from_str(value).ok()?);
+            }
+        }
+        let status = entry
+            .get("response")
+            .and_then(|response| response.get("status"))
+            .and_then(Value::as_u64)
+            .map(|status| status as u16);
+        Some(RecordedRequest {
+            method,
+            url,
+            headers,
+            status,
+        })
+    }
+}
+
+impl Replay {
+    // requests go out as concurrently as a scan's would, but are reported in the recorded order
+    pub async fn run(settings: &RinzlerSettings, requests: Vec<RecordedRequest>) -> Replay {
+        let (throttle, _, progress) = RinzlerApplication::shared_state(settings);
+        let host_limiter = Arc::new(HostLimiter::new(
+            settings.max_per_host,
+            settings.per_host_delay,
+        ));
+        let client = RinzlerClient::new(settings, throttle, host_limiter, progress);
+        let requests = stream::iter(requests)
+            .map(|recorded| {
+                let client = client.clone();
+                async move {
+                    let mut ct = CrawlTarget::new();
+                    ct.url = recorded.url.clone();
+                    let status = client
+                        .send(
+                            &mut ct,
+                            recorded.method.clone(),
+                            None,
+                            recorded.headers.clone(),
+                            RequestOptions::without_redirects(),
+                        )
+                        .await
+                        .ok()
+                        .map(|res| res.status().as_u16());
+                    (recorded, status)
+                }
+            })
+            .buffered(settings.max_threads)
+            .collect()
+            .await;
+        Replay { requests }
+    }
+
+    pub fn render(&self, format: DiffFormat) -> String {
+        match format {
+            DiffFormat::Text => self.to_text(),
+            DiffFormat::Json => self.to_json().to_string(),
+        }
+    }
+
+    fn changed(&self) -> usize {
+        self.requests
+            .iter()
+            .filter(|(recorded, status)| recorded.status != *status)
+            .count()
+    }
+
+    fn to_text(&self) -> String {
+        let mut lines = vec![];
+        for (recorded, status) in &self.requests {
+            let marker = match recorded.status == *status {
+                true => " ".normal(),
+                false => "~".yellow(),
+            };
+            lines.push(format!(
+                "{} {} -> {} {} {}",
+                marker,
+                fmt_status(recorded.status),
+                fmt_status(*status),
+                recorded.method,
+                recorded.url
+            ));
+        }
+        lines.push(format!(
+            "{} replayed, {} changed",
+            self.requests.len(),
+            self.changed()
+        ));
+        lines.join("\n")
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "replayed": self.requests.iter()
+                .map(|(recorded, status)| json!({
+                    "method": recorded.method.as_str(),
+                    "url": recorded.url,
+                    "recorded_status": recorded.status,
+                    "status": status,
+                    "changed": recorded.status != *status,
+                }))
+                .collect::<Vec<Value>>(),
+            "changed": self.changed(),
+        })
+    }
+}
+
+fn fmt_status(status: Option<u16>) -> String {
+    status
+        .map(|status| status.to_string())
+        .unwrap_or_else(|| "???".to_string())
+}
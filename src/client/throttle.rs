@@ -0,0 +1,189 @@
+use chrono::{DateTime, Utc};
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Response, StatusCode};
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time;
+use tracing::{info, warn};
+
+const MIN_BACKOFF_MS: u64 = 100;
+const MAX_BACKOFF_MS: u64 = 10_000;
+const MAX_RETRY_AFTER_SECS: u64 = 120;
+
+pub struct Throttle {
+    delay_ms: AtomicU64,
+    base_delay_ms: u64,
+    consecutive_errors: AtomicU32,
+    errors_before_slowdown: Option<u32>,
+    slowed_by_errors: AtomicBool,
+    limiter: Option<DefaultDirectRateLimiter>,
+    paused: AtomicBool,
+    resumed: Notify,
+    jitter_ms: u64,
+    adaptive: bool,
+    rng: Mutex<StdRng>,
+}
+
+impl Throttle {
+    pub fn new(
+        delay_ms: u64,
+        requests_per_second: Option<NonZeroU32>,
+        jitter_ms: u64,
+        adaptive: bool,
+        seed: Option<u64>,
+        errors_before_slowdown: Option<u32>,
+    ) -> Throttle {
+        Throttle {
+            delay_ms: AtomicU64::new(delay_ms),
+            base_delay_ms: delay_ms,
+            consecutive_errors: AtomicU32::new(0),
+            errors_before_slowdown,
+            slowed_by_errors: AtomicBool::new(false),
+            limiter: requests_per_second.map(|rps| RateLimiter::direct(Quota::per_second(rps))),
+            paused: AtomicBool::new(false),
+            resumed: Notify::new(),
+            jitter_ms,
+            adaptive,
+            rng: Mutex::new(match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            }),
+        }
+    }
+
+    pub async fn wait(&self) {
+        loop {
+            self.wait_while_paused().await;
+            // the bucket is shared by every request, unlike the delay which each request sleeps through
+            if let Some(limiter) = &self.limiter {
+                limiter.until_ready().await;
+            }
+            let delay = self.jittered(self.delay_ms.load(Ordering::Relaxed));
+            if delay > 0 {
+                time::sleep(Duration::from_millis(delay)).await;
+            }
+            // requests already queued on the limiter or delay when paused go round again,
+            // so resuming doesn't release them all at once
+            if !self.paused.load(Ordering::Acquire) {
+                break;
+            }
+        }
+    }
+
+    async fn wait_while_paused(&self) {
+        loop {
+            let resumed = self.resumed.notified();
+            if !self.paused.load(Ordering::Acquire) {
+                break;
+            }
+            resumed.await;
+        }
+    }
+
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Release);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Release);
+        self.resumed.notify_waiters();
+    }
+
+    fn jittered(&self, delay: u64) -> u64 {
+        if self.jitter_ms == 0 {
+            return delay;
+        }
+        let offset = self
+            .rng
+            .lock()
+            .unwrap()
+            .gen_range(-(self.jitter_ms as i64)..=self.jitter_ms as i64);
+        (delay as i64 + offset).max(0) as u64
+    }
+
+    pub fn is_overloaded(res: &Response) -> bool {
+        matches!(
+            res.status(),
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+        )
+    }
+
+    pub fn back_off(&self) {
+        if !self.adaptive {
+            return;
+        }
+        let current = self.delay_ms.load(Ordering::Relaxed);
+        let next = (current * 2).clamp(MIN_BACKOFF_MS, MAX_BACKOFF_MS.max(current));
+        if next != current {
+            self.delay_ms.store(next, Ordering::Relaxed);
+            warn!(
+                "Server signalled overload, throttling to {}ms per request",
+                next
+            );
+        }
+    }
+
+    /// Counts a request that got no response. Every `errors_before_slowdown` failures in a row
+    /// double the delay, as a run of resets or timeouts usually means the target or a WAF is
+    /// throttling us.
+    pub fn failed(&self) {
+        let threshold = match self.errors_before_slowdown {
+            Some(threshold) => threshold,
+            None => return,
+        };
+        let errors = self.consecutive_errors.fetch_add(1, Ordering::Relaxed) + 1;
+        if !errors.is_multiple_of(threshold) {
+            return;
+        }
+        let current = self.delay_ms.load(Ordering::Relaxed);
+        let next = (current * 2).clamp(MIN_BACKOFF_MS, MAX_BACKOFF_MS.max(current));
+        self.slowed_by_errors.store(true, Ordering::Relaxed);
+        if next != current {
+            self.delay_ms.store(next, Ordering::Relaxed);
+            warn!(
+                "{} requests failed in a row, slowing to {}ms per request",
+                errors, next
+            );
+        }
+    }
+
+    // each success halves the extra delay, so the rate recovers quickly but not all at once
+    pub fn succeeded(&self) {
+        if self.errors_before_slowdown.is_none() {
+            return;
+        }
+        self.consecutive_errors.store(0, Ordering::Relaxed);
+        if !self.slowed_by_errors.load(Ordering::Relaxed) {
+            return;
+        }
+        let current = self.delay_ms.load(Ordering::Relaxed);
+        let next = self.base_delay_ms + (current.saturating_sub(self.base_delay_ms)) / 2;
+        self.delay_ms.store(next, Ordering::Relaxed);
+        if next == self.base_delay_ms {
+            self.slowed_by_errors.store(false, Ordering::Relaxed);
+            info!(
+                "Requests are succeeding again, back to {}ms per request",
+                next
+            );
+        }
+    }
+}
+
+pub fn retry_after(res: &Response) -> Option<Duration> {
+    let value = res.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    let secs = match value.parse::<u64>() {
+        Ok(secs) => secs,
+        Err(_) => {
+            let date = DateTime::parse_from_rfc2822(value).ok()?;
+            let wait = date.with_timezone(&Utc) - Utc::now();
+            wait.num_seconds().max(0) as u64
+        }
+    };
+    Some(Duration::from_secs(secs.min(MAX_RETRY_AFTER_SECS)))
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::fmt::{Display, Formatter};
+use tokio::net::TcpStream;
+use url::Url;
+use x509_parser::extensions::GeneralName;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+const EXPIRY_WARNING_DAYS: i64 = 30;
+
+pub struct TlsInfo {
+    pub subject: String,
+    pub issuer: String,
+    pub sans: Vec<String>,
+    pub not_after: DateTime<Utc>,
+}
+
+impl TlsInfo {
+    // handshakes separately from the crawl's client, which doesn't expose the peer certificate
+    pub async fn fetch(url: &Url) -> Result<TlsInfo, String> {
+        // IPv6 literals are bracketed in URLs but not when connecting
+        let host = url
+            .host_str()
+            .ok_or("no host to connect to")?
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        let port = url.port_or_known_default().unwrap_or(443);
+        let connector = native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let stream = TcpStream::connect((host, port))
+            .await
+            .map_err(|e| e.to_string())?;
+        let tls = tokio_native_tls::TlsConnector::from(connector)
+            .connect(host, stream)
+            .await
+            .map_err(|e| e.to_string())?;
+        let der = tls
+            .get_ref()
+            .peer_certificate()
+            .map_err(|e| e.to_string())?
+            .ok_or("the server didn't present a certificate")?
+            .to_der()
+            .map_err(|e| e.to_string())?;
+        let (_, cert) = X509Certificate::from_der(&der).map_err(|e| e.to_string())?;
+
+        let sans = match cert.subject_alternative_name() {
+            Ok(Some(san)) => san
+                .value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(dns) => Some(dns.to_string()),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        };
+        Ok(TlsInfo {
+            subject: cert.subject().to_string(),
+            issuer: cert.issuer().to_string(),
+            sans,
+            not_after: Utc
+                .timestamp_opt(cert.validity().not_after.timestamp(), 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+        })
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.not_after < Utc::now()
+    }
+
+    pub fn expires_soon(&self) -> bool {
+        self.not_after < Utc::now() + Duration::days(EXPIRY_WARNING_DAYS)
+    }
+}
+
+impl Display for TlsInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "subject: {}, issuer: {}, SANs: {}, expires: {}",
+            self.subject,
+            self.issuer,
+            self.sans.join(" "),
+            self.not_after.format("%Y-%m-%d")
+        )
+    }
+}
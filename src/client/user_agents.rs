@@ -0,0 +1,14 @@
+use once_cell::sync::Lazy;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+static USER_AGENTS: Lazy<Vec<&'static str>> = Lazy::new(|| {
+    include_str!("user_agents.txt")
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+        .collect()
+});
+
+pub fn random<R: Rng>(rng: &mut R) -> &'static str {
+    USER_AGENTS.choose(rng).unwrap()
+}
@@ -0,0 +1,93 @@
+use crate::config::status_matcher::StatusMatcher;
+use crate::crawler::crawl_progress::CrawlProgress;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// What --fail-on is waiting for: a number of findings, or a finding with a particular status.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FailOn {
+    Count(usize),
+    Status(Vec<StatusMatcher>),
+}
+
+impl FailOn {
+    pub fn is_reached(&self, progress: &CrawlProgress) -> bool {
+        match self {
+            FailOn::Count(count) => progress.findings() >= *count,
+            FailOn::Status(statuses) => progress.findings_matching(statuses) > 0,
+        }
+    }
+}
+
+// a bare number is a count, as statuses are always spelled out with "status:"
+impl FromStr for FailOn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(statuses) = s.strip_prefix("status:") {
+            let statuses = StatusMatcher::parse_list(statuses)?;
+            if statuses.is_empty() {
+                return Err("'status:' needs at least one status code".to_string());
+            }
+            return Ok(FailOn::Status(statuses));
+        }
+        let count = s.strip_prefix("count:").unwrap_or(s);
+        match count.trim().parse::<usize>() {
+            Ok(0) => Err("a count of 0 would fail every scan".to_string()),
+            Ok(count) => Ok(FailOn::Count(count)),
+            Err(_) => Err(format!(
+                "'{}' should be a count, or status: followed by status codes",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for FailOn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailOn::Count(count) => write!(f, "{} finding(s)", count),
+            FailOn::Status(statuses) => {
+                let statuses: Vec<String> = statuses.iter().map(|s| s.to_string()).collect();
+                write!(f, "any {} finding", statuses.join(","))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_bare_number_is_a_count() {
+        assert_eq!("200".parse(), Ok(FailOn::Count(200)));
+        assert_eq!("count:3".parse(), Ok(FailOn::Count(3)));
+    }
+
+    #[test]
+    fn statuses_are_prefixed() {
+        assert_eq!(
+            "status:200,500-599".parse(),
+            Ok(FailOn::Status(vec![
+                StatusMatcher::Exact(200),
+                StatusMatcher::Range(500, 599)
+            ]))
+        );
+    }
+
+    #[test]
+    fn rejects_a_count_of_zero() {
+        assert!("0".parse::<FailOn>().is_err());
+        assert!("count:0".parse::<FailOn>().is_err());
+    }
+
+    #[test]
+    fn rejects_anything_else() {
+        assert!("500-599".parse::<FailOn>().is_err());
+        assert!("status:".parse::<FailOn>().is_err());
+        assert!("status:abc".parse::<FailOn>().is_err());
+        assert!("count:-1".parse::<FailOn>().is_err());
+    }
+}
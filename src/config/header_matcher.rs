@@ -0,0 +1,73 @@
+use regex::{Regex, RegexBuilder};
+use reqwest::header::HeaderMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Clone, Debug)]
+pub enum HeaderValueMatcher {
+    Any,
+    Contains(String),
+    Pattern(Regex),
+}
+
+#[derive(Clone, Debug)]
+pub struct HeaderMatcher {
+    name: String,
+    value: HeaderValueMatcher,
+}
+
+impl HeaderMatcher {
+    // a header sent more than once matches when any of its values does
+    pub fn matches(&self, headers: &HeaderMap) -> bool {
+        headers
+            .get_all(self.name.as_str())
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| match &self.value {
+                HeaderValueMatcher::Any => true,
+                HeaderValueMatcher::Contains(part) => {
+                    value.to_lowercase().contains(&part.to_lowercase())
+                }
+                HeaderValueMatcher::Pattern(pattern) => pattern.is_match(value),
+            })
+    }
+}
+
+impl FromStr for HeaderMatcher {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = match s.split_once(':') {
+            Some((name, value)) => (name.trim(), value.trim()),
+            None => (s.trim(), ""),
+        };
+        if name.is_empty() || reqwest::header::HeaderName::from_str(name).is_err() {
+            return Err(format!("'{}' isn't a valid header name", name));
+        }
+        let value = if value.is_empty() {
+            HeaderValueMatcher::Any
+        } else if value.len() > 1 && value.starts_with('/') && value.ends_with('/') {
+            let pattern = RegexBuilder::new(&value[1..value.len() - 1])
+                .case_insensitive(true)
+                .build()
+                .map_err(|why| format!("'{}' isn't a valid pattern: {}", value, why))?;
+            HeaderValueMatcher::Pattern(pattern)
+        } else {
+            HeaderValueMatcher::Contains(value.to_string())
+        };
+        Ok(HeaderMatcher {
+            name: name.to_lowercase(),
+            value,
+        })
+    }
+}
+
+impl Display for HeaderMatcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.value {
+            HeaderValueMatcher::Any => write!(f, "{}", self.name),
+            HeaderValueMatcher::Contains(part) => write!(f, "{}: {}", self.name, part),
+            HeaderValueMatcher::Pattern(pattern) => write!(f, "{}: /{}/", self.name, pattern),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatusMatcher {
+    Exact(u16),
+    Range(u16, u16),
+}
+
+impl StatusMatcher {
+    pub fn matches(&self, code: u16) -> bool {
+        match *self {
+            StatusMatcher::Exact(expected) => code == expected,
+            StatusMatcher::Range(low, high) => (low..=high).contains(&code),
+        }
+    }
+
+    pub fn parse_list(s: &str) -> Result<Vec<StatusMatcher>, String> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .map(StatusMatcher::from_str)
+            .collect()
+    }
+}
+
+impl FromStr for StatusMatcher {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_code = |code: &str| {
+            code.trim()
+                .parse::<u16>()
+                .map_err(|_| format!("'{}' isn't a valid status code", code.trim()))
+        };
+        match s.split_once('-') {
+            Some((low, high)) => {
+                let (low, high) = (parse_code(low)?, parse_code(high)?);
+                if low > high {
+                    return Err(format!("'{}' is an empty range", s));
+                }
+                Ok(StatusMatcher::Range(low, high))
+            }
+            None => Ok(StatusMatcher::Exact(parse_code(s)?)),
+        }
+    }
+}
+
+impl Display for StatusMatcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatusMatcher::Exact(code) => write!(f, "{}", code),
+            StatusMatcher::Range(low, high) => write!(f, "{}-{}", low, high),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use encoding_rs::{Encoding, UTF_8};
+use once_cell::sync::Lazy;
+use regex::bytes::Regex;
+
+// browsers only look this far into a page for a <meta charset>
+const META_SNIFF_BYTES: usize = 1024;
+
+static CHARSET_FINDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)charset\s*=\s*["']?([A-Za-z0-9_:.-]+)"#).unwrap());
+
+pub fn decode(content_type: Option<&str>, body: &[u8]) -> String {
+    let encoding = content_type
+        .and_then(|content_type| find_charset(content_type.as_bytes()))
+        .or_else(|| find_charset(&body[..body.len().min(META_SNIFF_BYTES)]))
+        .unwrap_or(UTF_8);
+    let (text, _, _) = encoding.decode(body);
+    text.into_owned()
+}
+
+fn find_charset(haystack: &[u8]) -> Option<&'static Encoding> {
+    let label = CHARSET_FINDER.captures(haystack)?.get(1)?;
+    Encoding::for_label(label.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_latin1_named_in_the_content_type() {
+        let body = b"<a href=\"/caf\xe9.html\">caf\xe9</a>";
+        assert_eq!(
+            decode(Some("text/html; charset=iso-8859-1"), body),
+            "<a href=\"/café.html\">café</a>"
+        );
+    }
+
+    #[test]
+    fn decodes_latin1_named_in_a_meta_tag() {
+        let body = b"<meta charset=\"ISO-8859-1\"><a href=\"/na\xefve.html\">";
+        assert_eq!(
+            decode(Some("text/html"), body),
+            "<meta charset=\"ISO-8859-1\"><a href=\"/naïve.html\">"
+        );
+    }
+}
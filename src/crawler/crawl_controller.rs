@@ -0,0 +1,71 @@
+use crossbeam::channel::Sender;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+pub enum ControllerMessageType {
+    Finished,
+    Error,
+}
+
+pub struct ControllerMessage {
+    pub message_type: ControllerMessageType,
+    pub data: String,
+}
+
+pub struct CrawlController {
+    sender: Sender<ControllerMessage>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Clone for CrawlController {
+    fn clone(&self) -> Self {
+        CrawlController {
+            sender: self.sender.clone(),
+            in_flight: Arc::clone(&self.in_flight),
+        }
+    }
+}
+
+impl CrawlController {
+    pub fn new(sender: Sender<ControllerMessage>) -> CrawlController {
+        CrawlController {
+            sender,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Registers a crawl as in flight until the returned guard is dropped. Once the last
+    /// crawl is dropped, even by a panic, the controller is told everything has finished.
+    pub fn start(&self) -> InFlight {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        InFlight {
+            controller: self.clone(),
+        }
+    }
+
+    pub fn fail(&self, why: String) {
+        let _ = self.sender.send(ControllerMessage {
+            message_type: ControllerMessageType::Error,
+            data: why,
+        });
+    }
+
+    fn finish(&self) {
+        if self.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            let _ = self.sender.send(ControllerMessage {
+                message_type: ControllerMessageType::Finished,
+                data: "".to_string(),
+            });
+        }
+    }
+}
+
+pub struct InFlight {
+    controller: CrawlController,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.controller.finish();
+    }
+}
@@ -0,0 +1,122 @@
+use crate::config::CrawlOrder;
+use crate::crawler::crawl_controller::InFlight;
+use crate::crawler::crawl_progress::CrawlProgress;
+use crate::crawler::crawl_target::CrawlTarget;
+use crate::crawler::rinzler_crawler::RinzlerCrawler;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+use tracing::debug;
+
+struct Queued {
+    crawler: RinzlerCrawler,
+    _in_flight: InFlight,
+}
+
+struct FrontierState {
+    queue: VecDeque<Queued>,
+    // every target ever queued, so none is crawled twice however many pages link to it
+    queued: HashSet<String>,
+    active: usize,
+    feeding: bool,
+}
+
+enum Next {
+    Crawl(Box<Queued>),
+    Wait,
+    Done,
+}
+
+pub struct CrawlFrontier {
+    order: CrawlOrder,
+    progress: Arc<CrawlProgress>,
+    state: Mutex<FrontierState>,
+    changed: Notify,
+}
+
+impl CrawlFrontier {
+    pub fn new(order: CrawlOrder, progress: Arc<CrawlProgress>) -> CrawlFrontier {
+        CrawlFrontier {
+            order,
+            progress,
+            state: Mutex::new(FrontierState {
+                queue: VecDeque::new(),
+                queued: HashSet::new(),
+                active: 0,
+                feeding: false,
+            }),
+            changed: Notify::new(),
+        }
+    }
+
+    /// Queues `crawler` unless its target has been queued before, counting it in the progress.
+    pub fn push(&self, crawler: RinzlerCrawler) {
+        let mut state = self.state.lock().unwrap();
+        if !state
+            .queued
+            .insert(CrawlTarget::normalize_url(crawler.target()))
+        {
+            debug!(
+                "Not queueing {}, it's already been queued",
+                crawler.target()
+            );
+            return;
+        }
+        // counted before the lock is let go, so no worker can start it first
+        self.progress.enqueue(1);
+        let queued = Queued {
+            _in_flight: crawler.controller.start(),
+            crawler,
+        };
+        state.queue.push_back(queued);
+        drop(state);
+        self.changed.notify_waiters();
+    }
+
+    /// Keeps the workers waiting for more, even with nothing queued or active, until
+    /// `stop_feeding` is called.
+    pub fn start_feeding(&self) {
+        self.state.lock().unwrap().feeding = true;
+    }
+
+    pub fn stop_feeding(&self) {
+        self.state.lock().unwrap().feeding = false;
+        self.changed.notify_waiters();
+    }
+
+    /// Crawls queued pages until there's nothing left and no other worker can queue more.
+    pub async fn work(&self, visited: Arc<Mutex<HashSet<String>>>) {
+        loop {
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            match self.next() {
+                Next::Crawl(queued) => {
+                    let _ = queued.crawler.crawl(Arc::clone(&visited)).await;
+                    drop(queued);
+                    self.state.lock().unwrap().active -= 1;
+                    self.changed.notify_waiters();
+                }
+                Next::Wait => changed.await,
+                Next::Done => return,
+            }
+        }
+    }
+
+    fn next(&self) -> Next {
+        let mut state = self.state.lock().unwrap();
+        let queued = match self.order {
+            CrawlOrder::BreadthFirst => state.queue.pop_front(),
+            CrawlOrder::DepthFirst => state.queue.pop_back(),
+        };
+        match queued {
+            Some(queued) => {
+                state.active += 1;
+                Next::Crawl(Box::new(queued))
+            }
+            None if state.active > 0 || state.feeding => Next::Wait,
+            None => Next::Done,
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::config::GraphFormat;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::sync::Mutex;
+
+pub struct CrawlGraph {
+    edges: Mutex<BTreeMap<String, BTreeSet<String>>>,
+}
+
+impl CrawlGraph {
+    pub fn new() -> CrawlGraph {
+        CrawlGraph {
+            edges: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn add_edge(&self, parent: &str, child: &str) {
+        self.edges
+            .lock()
+            .unwrap()
+            .entry(parent.to_string())
+            .or_default()
+            .insert(child.to_string());
+    }
+
+    pub fn write(&self, path: &str, format: GraphFormat) -> io::Result<()> {
+        let contents = match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Json => self.to_json()?,
+        };
+        fs::write(path, contents)
+    }
+
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph rinzler {\n");
+        for (parent, children) in self.edges.lock().unwrap().iter() {
+            for child in children {
+                dot.push_str(&format!("    {:?} -> {:?};\n", parent, child));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn to_json(&self) -> io::Result<String> {
+        Ok(serde_json::to_string_pretty(&*self.edges.lock().unwrap())?)
+    }
+}
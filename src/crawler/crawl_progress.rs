@@ -0,0 +1,132 @@
+use crate::config::status_matcher::StatusMatcher;
+use crate::crawler::request_errors::{RequestError, RequestErrors};
+use crate::crawler::response_times::{self, ResponseTimes, Timings};
+use reqwest::Url;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub struct CrawlProgress {
+    queued: AtomicUsize,
+    crawled: AtomicUsize,
+    findings: AtomicUsize,
+    // how many findings had each status, so --fail-on can look for a particular one
+    finding_statuses: Mutex<HashMap<u16, usize>>,
+    requests: AtomicUsize,
+    errors: AtomicUsize,
+    response_times: ResponseTimes,
+    failures: RequestErrors,
+    started: Instant,
+}
+
+impl CrawlProgress {
+    pub fn new() -> CrawlProgress {
+        CrawlProgress {
+            queued: AtomicUsize::new(0),
+            crawled: AtomicUsize::new(0),
+            findings: AtomicUsize::new(0),
+            finding_statuses: Mutex::new(HashMap::new()),
+            requests: AtomicUsize::new(0),
+            errors: AtomicUsize::new(0),
+            response_times: ResponseTimes::new(),
+            failures: RequestErrors::new(),
+            started: Instant::now(),
+        }
+    }
+
+    pub fn enqueue(&self, count: usize) {
+        self.queued.fetch_add(count, Ordering::SeqCst);
+    }
+
+    pub fn start(&self) {
+        let _ = self
+            .queued
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |q| q.checked_sub(1));
+    }
+
+    pub fn complete(&self) {
+        self.crawled.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn found(&self, status: Option<u16>) {
+        self.findings.fetch_add(1, Ordering::SeqCst);
+        if let Some(status) = status {
+            *self
+                .finding_statuses
+                .lock()
+                .unwrap()
+                .entry(status)
+                .or_default() += 1;
+        }
+    }
+
+    pub fn findings(&self) -> usize {
+        self.findings.load(Ordering::SeqCst)
+    }
+
+    pub fn findings_matching(&self, statuses: &[StatusMatcher]) -> usize {
+        self.finding_statuses
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(status, _)| statuses.iter().any(|matcher| matcher.matches(**status)))
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    pub fn requested(&self) {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn failed(&self, url: &str, error: &reqwest::Error) {
+        self.errors.fetch_add(1, Ordering::SeqCst);
+        self.failures.record(RequestError::new(url, error));
+    }
+
+    pub fn failures_since(&self, seen: usize) -> Vec<RequestError> {
+        self.failures.since(seen)
+    }
+
+    pub fn responded(&self, url: &Url, elapsed: Duration) {
+        self.response_times
+            .record(response_times::host_of(url), elapsed.as_millis() as u64);
+    }
+
+    pub fn timings(&self) -> Vec<Timings> {
+        self.response_times.summarize()
+    }
+
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    pub fn errors(&self) -> usize {
+        self.errors.load(Ordering::SeqCst)
+    }
+
+    pub fn crawled(&self) -> usize {
+        self.crawled.load(Ordering::SeqCst)
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    pub fn requests_per_second(&self) -> f64 {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        if elapsed > 0.0 {
+            self.requests() as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} crawled, {} queued",
+            self.crawled.load(Ordering::SeqCst),
+            self.queued.load(Ordering::SeqCst)
+        )
+    }
+}
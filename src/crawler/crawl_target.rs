@@ -1,7 +1,7 @@
 use chrono::{DateTime, Local};
 use colored::{ColoredString, Colorize};
-use reqwest::blocking::Response;
-use reqwest::{Method, Url};
+use reqwest::{Method, Response, Url};
+use serde_json::{json, Value};
 use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
 use uuid::Uuid;
@@ -11,6 +11,9 @@ pub struct CrawlTarget {
     pub status_code: Option<u16>,
     pub url: String,
     pub method: String,
+    pub redirect_chain: Vec<(u16, String)>,
+    pub response_time_ms: Option<u64>,
+    pub content_encoding: Option<String>,
     pub(crate) timestamp: DateTime<Local>,
 }
 
@@ -21,6 +24,9 @@ impl CrawlTarget {
             status_code: None,
             url: u.to_string(),
             method: String::default(),
+            redirect_chain: vec![],
+            response_time_ms: None,
+            content_encoding: None,
             timestamp: Local::now(),
         }
     }
@@ -62,22 +68,30 @@ impl Eq for CrawlTarget {}
 impl Hash for CrawlTarget {
     fn hash<H: Hasher>(&self, state: &mut H) {
         state.write(self.id.as_bytes());
-        state.finish();
     }
 }
 
 impl Clone for CrawlTarget {
     fn clone(&self) -> Self {
         CrawlTarget {
-            id: self.id.clone(),
-            status_code: self.status_code.clone(),
+            id: self.id,
+            status_code: self.status_code,
             url: self.url.clone(),
             method: self.method.clone(),
-            timestamp: self.timestamp.clone(),
+            redirect_chain: self.redirect_chain.clone(),
+            response_time_ms: self.response_time_ms,
+            content_encoding: self.content_encoding.clone(),
+            timestamp: self.timestamp,
         }
     }
 }
 
+impl Default for CrawlTarget {
+    fn default() -> Self {
+        CrawlTarget::new()
+    }
+}
+
 impl CrawlTarget {
     pub fn new() -> CrawlTarget {
         CrawlTarget {
@@ -85,20 +99,67 @@ impl CrawlTarget {
             status_code: None,
             url: String::default(),
             method: Method::HEAD.to_string(),
+            redirect_chain: vec![],
+            response_time_ms: None,
+            content_encoding: None,
             timestamp: Local::now(),
         }
     }
 
+    #[allow(dead_code)]
     pub fn from_response(res: Response) -> CrawlTarget {
         CrawlTarget {
             id: Uuid::new_v4(),
             status_code: Some(res.status().as_u16()),
             url: res.url().to_string(),
             method: String::default(),
+            redirect_chain: vec![],
+            response_time_ms: None,
+            content_encoding: None,
             timestamp: Local::now(),
         }
     }
 
+    pub fn to_json(&self) -> Value {
+        json!({
+            "url": self.url,
+            "status": self.status_code,
+            "method": self.method,
+            "response_time_ms": self.response_time_ms,
+            "content_encoding": self.content_encoding,
+            "timestamp": self.timestamp.to_rfc3339(),
+        })
+    }
+
+    // the same page can be written differently, e.g. with an explicit default port or a fragment
+    pub fn normalize_url(url: &str) -> String {
+        match Url::parse(url) {
+            Ok(mut url) => {
+                url.set_fragment(None);
+                url.to_string()
+            }
+            Err(_) => url.to_string(),
+        }
+    }
+
+    // the inverse of to_json, for reading back the results of an earlier scan
+    pub fn from_json(value: &Value) -> Option<CrawlTarget> {
+        Some(CrawlTarget {
+            id: Uuid::new_v4(),
+            status_code: value["status"].as_u64().map(|status| status as u16),
+            url: value["url"].as_str()?.to_string(),
+            method: value["method"].as_str().unwrap_or_default().to_string(),
+            redirect_chain: vec![],
+            response_time_ms: value["response_time_ms"].as_u64(),
+            content_encoding: value["content_encoding"].as_str().map(String::from),
+            timestamp: value["timestamp"]
+                .as_str()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&Local))
+                .unwrap_or_else(Local::now),
+        })
+    }
+
     fn fmt_status_code(status_code: u16) -> ColoredString {
         match status_code {
             0..=199 => status_code.to_string().as_str().bright_white(),
@@ -0,0 +1,64 @@
+use once_cell::sync::Lazy;
+use scraper::{Html, Selector};
+use std::fmt::{Display, Formatter};
+use url::Url;
+
+static FORM: Lazy<Selector> = Lazy::new(|| Selector::parse("form").unwrap());
+static FIELD: Lazy<Selector> = Lazy::new(|| {
+    Selector::parse("input[name], select[name], textarea[name], button[name]").unwrap()
+});
+
+pub struct HtmlForm {
+    pub action: Url,
+    pub method: String,
+    pub fields: Vec<String>,
+}
+
+impl HtmlForm {
+    pub fn find_all(page: &Url, body: &str) -> Vec<HtmlForm> {
+        let document = Html::parse_document(body);
+        document
+            .select(&FORM)
+            .map(|form| {
+                // a form without an action submits back to the page it's on
+                let action = form
+                    .value()
+                    .attr("action")
+                    .filter(|action| !action.trim().is_empty())
+                    .and_then(|action| page.join(action.trim()).ok())
+                    .unwrap_or_else(|| page.clone());
+                let method = form
+                    .value()
+                    .attr("method")
+                    .unwrap_or("get")
+                    .trim()
+                    .to_uppercase();
+                let mut fields: Vec<String> = vec![];
+                for field in form.select(&FIELD) {
+                    if let Some(name) = field.value().attr("name") {
+                        if !fields.iter().any(|f| f == name) {
+                            fields.push(name.to_string());
+                        }
+                    }
+                }
+                HtmlForm {
+                    action,
+                    method,
+                    fields,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Display for HtmlForm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} [{}]",
+            self.method,
+            self.action,
+            self.fields.join(", ")
+        )
+    }
+}
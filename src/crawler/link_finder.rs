@@ -0,0 +1,43 @@
+use regex::Regex;
+use url::Url;
+
+pub struct LinkFinder {
+    pattern: Regex,
+}
+
+impl LinkFinder {
+    // an attribute only matches on its own, so src doesn't also pick up data-src
+    pub fn new(attrs: &[String]) -> LinkFinder {
+        let names: Vec<String> = attrs.iter().map(|attr| regex::escape(attr)).collect();
+        let pattern = format!(
+            r#"(?i)(?:^|[^\w-])({})\s*=\s*["']([^"'<>]*)"#,
+            names.join("|")
+        );
+        LinkFinder {
+            pattern: Regex::new(&pattern).unwrap(),
+        }
+    }
+
+    pub fn find(&self, url: &Url, body: &str) -> Vec<Url> {
+        let mut links: Vec<Url> = vec![];
+        for captures in self.pattern.captures_iter(body) {
+            let value = &captures[2];
+            let candidates: Vec<&str> = match captures[1].eq_ignore_ascii_case("srcset") {
+                // each candidate is a url followed by an optional width or density
+                true => value
+                    .split(',')
+                    .filter_map(|candidate| candidate.split_whitespace().next())
+                    .collect(),
+                false => value.split(',').take(1).collect(),
+            };
+            for candidate in candidates {
+                if let Ok(link) = url.join(candidate.trim()) {
+                    if !links.contains(&link) {
+                        links.push(link);
+                    }
+                }
+            }
+        }
+        links
+    }
+}
@@ -1,2 +1,18 @@
+pub(crate) mod charset;
+pub(crate) mod crawl_controller;
+pub(crate) mod crawl_frontier;
+pub(crate) mod crawl_graph;
+pub(crate) mod crawl_progress;
 pub(crate) mod crawl_target;
+pub(crate) mod html_form;
+pub(crate) mod link_finder;
+pub(crate) mod openapi;
+pub(crate) mod request_errors;
+pub(crate) mod response_cache;
+pub(crate) mod response_times;
 pub(crate) mod rinzler_crawler;
+pub(crate) mod security_finding;
+pub(crate) mod skipped_targets;
+pub(crate) mod soft_404;
+pub(crate) mod source_map;
+pub(crate) mod word_mutator;
@@ -0,0 +1,106 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Method;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use url::Url;
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+const PATH_PARAM_PLACEHOLDER: &str = "1";
+
+static TEMPLATE_VARIABLE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([^}]*)\}").unwrap());
+
+pub struct ApiEndpoint {
+    pub method: Method,
+    pub path: String,
+    expected: Vec<String>,
+}
+
+impl ApiEndpoint {
+    // an operation that documents no responses, or a default one, can't answer unexpectedly
+    pub fn is_expected(&self, status_code: u16) -> bool {
+        let status = status_code.to_string();
+        let range = format!("{}XX", &status[..1]);
+        self.expected.is_empty()
+            || self.expected.iter().any(|documented| {
+                documented == "default"
+                    || documented == &status
+                    || documented.eq_ignore_ascii_case(&range)
+            })
+    }
+
+    pub fn documented(&self) -> String {
+        self.expected.join(", ")
+    }
+}
+
+impl Display for ApiEndpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.method, self.path)
+    }
+}
+
+pub fn parse(spec: &str) -> Result<Value, String> {
+    serde_json::from_str(spec)
+        .or_else(|_| serde_yaml::from_str(spec).map_err(|why| format!("not JSON or YAML: {}", why)))
+}
+
+pub fn endpoints(spec: &Value) -> Vec<ApiEndpoint> {
+    let base_path = base_path(spec);
+    let mut endpoints = vec![];
+    let paths = match spec.get("paths").and_then(Value::as_object) {
+        Some(paths) => paths,
+        None => return endpoints,
+    };
+    for (path, operations) in paths {
+        let path = format!(
+            "{}/{}",
+            base_path.trim_end_matches('/'),
+            TEMPLATE_VARIABLE
+                .replace_all(path, PATH_PARAM_PLACEHOLDER)
+                .trim_start_matches('/')
+        );
+        for method in METHODS {
+            if let Some(operation) = operations.get(method) {
+                endpoints.push(ApiEndpoint {
+                    method: method.to_uppercase().parse().unwrap(),
+                    path: path.clone(),
+                    expected: operation
+                        .get("responses")
+                        .and_then(Value::as_object)
+                        .map(|responses| responses.keys().cloned().collect())
+                        .unwrap_or_default(),
+                });
+            }
+        }
+    }
+    endpoints
+}
+
+// swagger 2 has a basePath, openapi 3 puts it in the first server's url
+fn base_path(spec: &Value) -> String {
+    if let Some(base_path) = spec.get("basePath").and_then(Value::as_str) {
+        return base_path.to_string();
+    }
+    let server = match spec.pointer("/servers/0") {
+        Some(server) => server,
+        None => return String::default(),
+    };
+    let template = server
+        .get("url")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let url = TEMPLATE_VARIABLE.replace_all(template, |captures: &regex::Captures| {
+        server
+            .pointer(&format!("/variables/{}/default", &captures[1]))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    });
+    match Url::parse(&url) {
+        Ok(url) => url.path().to_string(),
+        Err(_) => url.to_string(),
+    }
+}
@@ -0,0 +1,116 @@
+use serde_json::{json, Value};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FailureKind {
+    Dns,
+    Refused,
+    Timeout,
+    ConnectTimeout,
+    Tls,
+    Connect,
+    Other,
+}
+
+impl Display for FailureKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailureKind::Dns => write!(f, "dns"),
+            FailureKind::Refused => write!(f, "connection refused"),
+            FailureKind::Timeout => write!(f, "timeout"),
+            FailureKind::ConnectTimeout => write!(f, "connect timeout"),
+            FailureKind::Tls => write!(f, "tls"),
+            FailureKind::Connect => write!(f, "connection"),
+            FailureKind::Other => write!(f, "other"),
+        }
+    }
+}
+
+pub struct RequestError {
+    pub url: String,
+    pub kind: FailureKind,
+    pub message: String,
+}
+
+impl RequestError {
+    pub fn new(url: &str, error: &reqwest::Error) -> RequestError {
+        // reqwest only says whether it failed to connect, the reason is further down the chain
+        let mut chain = error.to_string();
+        let mut message = chain.clone();
+        let mut source = error.source();
+        while let Some(cause) = source {
+            message = cause.to_string();
+            chain = format!("{}: {}", chain, message);
+            source = cause.source();
+        }
+        let lower = chain.to_lowercase();
+        // a host that never let us connect is a different problem to one that's slow to answer
+        let kind = if error.is_timeout() && error.is_connect() {
+            FailureKind::ConnectTimeout
+        } else if error.is_timeout() {
+            FailureKind::Timeout
+        } else if lower.contains("dns error") || lower.contains("failed to lookup address") {
+            FailureKind::Dns
+        } else if lower.contains("certificate") || lower.contains("tls") || lower.contains("ssl") {
+            FailureKind::Tls
+        } else if lower.contains("connection refused") {
+            FailureKind::Refused
+        } else if error.is_connect() {
+            FailureKind::Connect
+        } else {
+            FailureKind::Other
+        };
+        RequestError {
+            url: url.to_string(),
+            kind,
+            message,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "url": self.url,
+            "kind": self.kind.to_string(),
+            "message": self.message,
+        })
+    }
+}
+
+impl Clone for RequestError {
+    fn clone(&self) -> Self {
+        RequestError {
+            url: self.url.clone(),
+            kind: self.kind,
+            message: self.message.clone(),
+        }
+    }
+}
+
+pub struct RequestErrors {
+    errors: Mutex<Vec<RequestError>>,
+}
+
+impl RequestErrors {
+    pub fn new() -> RequestErrors {
+        RequestErrors {
+            errors: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn record(&self, error: RequestError) {
+        self.errors.lock().unwrap().push(error);
+    }
+
+    /// Every error after the first `seen`, so they can be shown as they happen.
+    pub fn since(&self, seen: usize) -> Vec<RequestError> {
+        self.errors
+            .lock()
+            .unwrap()
+            .iter()
+            .skip(seen)
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,82 @@
+use reqwest::header::{HeaderMap, ETAG, LAST_MODIFIED};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use tracing::warn;
+use url::Url;
+use uuid::Uuid;
+
+pub struct CacheEntry {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub links: Vec<Url>,
+}
+
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+impl Clone for ResponseCache {
+    fn clone(&self) -> Self {
+        ResponseCache {
+            dir: self.dir.clone(),
+        }
+    }
+}
+
+impl ResponseCache {
+    pub fn new(dir: &str) -> ResponseCache {
+        ResponseCache {
+            dir: PathBuf::from(dir),
+        }
+    }
+
+    pub fn load(&self, url: &str) -> Option<CacheEntry> {
+        let contents = fs::read_to_string(self.path_for(url)).ok()?;
+        let mut lines = contents.lines();
+        if lines.next()? != url {
+            return None;
+        }
+        let etag = lines.next().filter(|l| !l.is_empty()).map(String::from);
+        let last_modified = lines.next().filter(|l| !l.is_empty()).map(String::from);
+        let links = lines.filter_map(|l| Url::parse(l).ok()).collect();
+
+        Some(CacheEntry {
+            etag,
+            last_modified,
+            links,
+        })
+    }
+
+    pub fn store(&self, url: &str, headers: &HeaderMap, links: &[Url]) {
+        let etag = ResponseCache::header_value(headers, ETAG);
+        let last_modified = ResponseCache::header_value(headers, LAST_MODIFIED);
+        if etag.is_empty() && last_modified.is_empty() {
+            return;
+        }
+
+        let mut contents = vec![url.to_string(), etag, last_modified];
+        contents.extend(links.iter().map(|l| l.to_string()));
+        if let Err(why) = self.write(url, contents.join("\n")) {
+            warn!("Couldn't cache {}: {}", url, why);
+        }
+    }
+
+    fn write(&self, url: &str, contents: String) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path_for(url), contents)
+    }
+
+    fn path_for(&self, url: &str) -> PathBuf {
+        self.dir
+            .join(Uuid::new_v5(&Uuid::NAMESPACE_URL, url.as_bytes()).to_string())
+    }
+
+    fn header_value(headers: &HeaderMap, name: reqwest::header::HeaderName) -> String {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    }
+}
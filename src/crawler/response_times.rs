@@ -0,0 +1,111 @@
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+use url::Url;
+
+pub const ALL_HOSTS: &str = "all";
+
+/// How timings and per host results name a host, with its port when one is given.
+pub fn host_of(url: &Url) -> String {
+    match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (host, _) => host.unwrap_or_default().to_string(),
+    }
+}
+
+pub struct ResponseTimes {
+    by_host: Mutex<BTreeMap<String, Vec<u64>>>,
+}
+
+impl ResponseTimes {
+    pub fn new() -> ResponseTimes {
+        ResponseTimes {
+            by_host: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn record(&self, host: String, millis: u64) {
+        self.by_host
+            .lock()
+            .unwrap()
+            .entry(host)
+            .or_default()
+            .push(millis);
+    }
+
+    /// One entry per host, followed by one for every host together.
+    pub fn summarize(&self) -> Vec<Timings> {
+        let by_host = self.by_host.lock().unwrap();
+        let mut timings: Vec<Timings> = by_host
+            .iter()
+            .filter_map(|(host, samples)| Timings::from_samples(host, samples.clone()))
+            .collect();
+        let everything: Vec<u64> = by_host.values().flatten().copied().collect();
+        timings.extend(Timings::from_samples(ALL_HOSTS, everything));
+        timings
+    }
+}
+
+pub struct Timings {
+    pub host: String,
+    pub count: usize,
+    pub min: u64,
+    pub median: u64,
+    pub p95: u64,
+    pub max: u64,
+}
+
+impl Timings {
+    fn from_samples(host: &str, mut samples: Vec<u64>) -> Option<Timings> {
+        samples.sort_unstable();
+        Some(Timings {
+            host: host.to_string(),
+            count: samples.len(),
+            min: *samples.first()?,
+            median: Timings::percentile(&samples, 50),
+            p95: Timings::percentile(&samples, 95),
+            max: *samples.last()?,
+        })
+    }
+
+    // nearest rank, so the result is always a time that was actually seen
+    fn percentile(sorted: &[u64], percent: usize) -> u64 {
+        let rank = (sorted.len() * percent).div_ceil(100).max(1);
+        sorted[rank - 1]
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "host": self.host,
+            "requests": self.count,
+            "min_ms": self.min,
+            "median_ms": self.median,
+            "p95_ms": self.p95,
+            "max_ms": self.max,
+        })
+    }
+}
+
+impl Clone for Timings {
+    fn clone(&self) -> Self {
+        Timings {
+            host: self.host.clone(),
+            count: self.count,
+            min: self.min,
+            median: self.median,
+            p95: self.p95,
+            max: self.max,
+        }
+    }
+}
+
+impl Display for Timings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<30} {:>8} {:>6} {:>6} {:>6} {:>6}",
+            self.host, self.count, self.min, self.median, self.p95, self.max
+        )
+    }
+}
@@ -1,299 +1,1699 @@
+use crate::client::tls_info::TlsInfo;
 use crate::client::{RequestOptions, RinzlerClient};
-use crate::config::{Flags, RinzlerSettings};
+use crate::config::{Flags, RinzlerSettings, FUZZ_KEYWORD};
+use crate::crawler::charset;
+use crate::crawler::crawl_controller::CrawlController;
+use crate::crawler::crawl_frontier::CrawlFrontier;
+use crate::crawler::crawl_graph::CrawlGraph;
+use crate::crawler::crawl_progress::CrawlProgress;
 use crate::crawler::crawl_target::CrawlTarget;
-use crate::ui::rinzler_console::{ConsoleMessage, ConsoleMessageType};
+use crate::crawler::html_form::HtmlForm;
+use crate::crawler::link_finder::LinkFinder;
+use crate::crawler::openapi::{self, ApiEndpoint};
+use crate::crawler::response_cache::ResponseCache;
+use crate::crawler::security_finding::{
+    SecurityFinding, SecurityFindings, SecurityRule, DANGEROUS_METHODS, EXPIRED_CERTIFICATE,
+    OPEN_REDIRECT, REDIRECT_LOOP, SENSITIVE_FILE, TRACE_ENABLED,
+};
+use crate::crawler::skipped_targets::SkippedTargets;
+use crate::crawler::soft_404::Soft404Signature;
+use crate::crawler::source_map;
+use crate::crawler::word_mutator::WordMutator;
+use crate::ui::rinzler_console::{ConsoleMessage, ConsoleMessageType, ConsoleSender};
+use crate::ui::webhook::Webhook;
 use chrono::Local;
 use crossbeam::channel::Sender;
-use rayon::prelude::*;
+use futures::stream::{self, StreamExt};
+use mime::Mime;
+use once_cell::sync::Lazy;
 use regex::Regex;
-use reqwest::blocking::Response;
-use reqwest::Result;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, Response};
+use reqwest::{Result, StatusCode};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
-use url::{ParseError, Url};
+use tracing::{debug, info, warn};
+use url::{form_urlencoded, ParseError, Url};
+use uuid::Uuid;
 
-pub enum ControllerMessageType {
-    FINISHED,
-    ERROR,
-}
+static COMMON_CHECKS: Lazy<Vec<String>> = Lazy::new(|| {
+    include_str!("common_checks.txt")
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+        .map(String::from)
+        .collect()
+});
+
+const REDIRECT_PARAMS: [&str; 10] = [
+    "url",
+    "next",
+    "redirect",
+    "redirect_uri",
+    "redirect_url",
+    "return",
+    "return_to",
+    "returnto",
+    "dest",
+    "continue",
+];
+const REDIRECT_CANARY: &str = "https://rinzler-canary.invalid/";
+const DANGEROUS_METHOD_NAMES: [&str; 3] = ["PUT", "DELETE", "PATCH"];
+// smaller bodies arrive too quickly for their progress to be worth drawing
+const DOWNLOAD_PROGRESS_MIN: u64 = 1024 * 1024;
+const DOWNLOAD_PROGRESS_STEP: u64 = 64 * 1024;
+
+static EMAIL_FINDER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}").unwrap()
+});
+static COMMENT_FINDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<!--(.*?)-->").unwrap());
 
-pub struct ControllerMessage {
-    pub message_type: ControllerMessageType,
-    pub data: String,
+static LINK_TAG_FINDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<link\b[^>]*>").unwrap());
+static CANONICAL_REL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)\brel\s*=\s*["']?canonical\b"#).unwrap());
+static META_TAG_FINDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<meta\b[^>]*>").unwrap());
+static REFRESH_EQUIV: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)\bhttp-equiv\s*=\s*["']?refresh\b"#).unwrap());
+static CONTENT_FINDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)\bcontent\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#).unwrap());
+// the delay is optional, as is 'url=', and the url itself may be quoted again
+static REFRESH_URL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?is)^\s*[\d.]*\s*[;,]?\s*(?:url\s*=\s*)?["']?([^"']*)["']?\s*$"#).unwrap()
+});
+static HREF_FINDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)\bhref\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#).unwrap());
+
+struct FuzzRequest {
+    url: Url,
+    method: Option<Method>,
+    body: Option<String>,
+    headers: HeaderMap,
 }
 
 pub struct RinzlerCrawler {
     target: String,
     settings: RinzlerSettings,
-    pub controller_sender: Sender<ControllerMessage>,
-    pub console_sender: Sender<ConsoleMessage>,
+    pub controller: CrawlController,
+    pub console_sender: ConsoleSender,
     scoped_domains: Vec<String>,
     pub client: RinzlerClient,
+    progress: Arc<CrawlProgress>,
+    graph: Arc<CrawlGraph>,
+    security_findings: Arc<SecurityFindings>,
+    webhook: Option<Webhook>,
+    frontier: Arc<CrawlFrontier>,
+    skipped: Arc<SkippedTargets>,
+    parent: Option<CrawlTarget>,
+    // a target given on the command line or read from stdin, rather than one found by crawling
+    root: bool,
+    cache: Option<ResponseCache>,
+    link_finder: Arc<LinkFinder>,
 }
 
 impl RinzlerCrawler {
-    pub(crate) fn finish(&self) {
-        let _ = self.controller_sender.send(ControllerMessage {
-            message_type: ControllerMessageType::FINISHED,
-            data: "".to_string(),
-        });
-    }
-}
-
-impl RinzlerCrawler {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         target: String,
         settings: RinzlerSettings,
-        controller_messages: Sender<ControllerMessage>,
+        controller: CrawlController,
         console_messages: Sender<ConsoleMessage>,
         scoped_domains: Vec<String>,
         client: RinzlerClient,
+        progress: Arc<CrawlProgress>,
+        graph: Arc<CrawlGraph>,
+        security_findings: Arc<SecurityFindings>,
+        webhook: Option<Webhook>,
+        frontier: Arc<CrawlFrontier>,
+        skipped: Arc<SkippedTargets>,
     ) -> RinzlerCrawler {
         RinzlerCrawler {
             target,
             settings: settings.to_owned(),
-            controller_sender: controller_messages,
-            console_sender: console_messages,
+            controller,
+            console_sender: ConsoleSender::new(console_messages),
             scoped_domains,
             client,
+            progress,
+            graph,
+            security_findings,
+            webhook,
+            frontier,
+            skipped,
+            parent: None,
+            root: true,
+            cache: settings.cache_dir.as_deref().map(ResponseCache::new),
+            link_finder: Arc::new(LinkFinder::new(&settings.link_attrs)),
         }
     }
 
-    pub(crate) fn crawl(&self, already_visited: Arc<std::sync::Mutex<Vec<String>>>) -> Result<()> {
+    pub(crate) async fn crawl(&self, already_visited: Arc<Mutex<HashSet<String>>>) -> Result<()> {
         let wordlist = &self.settings.wordlist;
         let target = &self.target;
         let mut crawl_target = CrawlTarget::new();
 
-        match Url::parse(&target) {
+        match Url::parse(target) {
             Ok(u) => {
                 crawl_target.url = u.to_string();
-                self.send_target_found_message(&mut crawl_target);
+                self.send_target_found_message(&mut crawl_target).await;
             }
             Err(why) => {
                 //we dont want to continue when bogus urls are supplied
-                self.send_abort_program_message(&target, why);
+                self.send_abort_program_message(&target, why).await;
                 return Ok(());
             }
         };
+        if self.settings.tls_info && self.root && crawl_target.url.starts_with("https:") {
+            self.report_tls_info(&crawl_target).await;
+        }
+        if self.settings.check_open_redirect {
+            self.check_open_redirects(&crawl_target).await;
+        }
+        if self.settings.check_methods && self.root {
+            self.check_methods(&crawl_target).await;
+        }
+        if let Some(spec) = &self.settings.openapi {
+            if self.root {
+                self.probe_openapi(spec, &crawl_target).await;
+            }
+        }
         let flags = &self.settings.flags;
+        // only the original targets are checked, not every page the crawl turns up
+        if self.settings.common_checks && self.root {
+            self.force_browse(&already_visited, crawl_target.clone(), &COMMON_CHECKS, true)
+                .await;
+        }
         if let Some(wordlist) = wordlist {
-            if flags.contains(Flags::BRUTE) {
-                self.force_browse(&already_visited, crawl_target.clone(), wordlist.to_owned());
+            if flags.intersects(Flags::BRUTE | Flags::FUZZ) {
+                self.force_browse(&already_visited, crawl_target.clone(), wordlist, false)
+                    .await;
             }
         }
         if flags.contains(Flags::CRAWL) {
-            self.find_new_urls(&already_visited, crawl_target.clone());
+            self.find_new_urls(&already_visited, crawl_target.clone())
+                .await;
+        }
+        if flags.contains(Flags::LINKS) {
+            self.extract_links(crawl_target).await;
         }
         Ok(())
     }
 
-    fn send_abort_program_message(&self, target: &&String, why: ParseError) {
-        let _ = self.console_sender.send(ConsoleMessage {
-            message_type: ConsoleMessageType::Abort,
-            data: Err(format!("Couldn't parse '{}' as a URL: {}", &target, why)),
-            original_target: None,
-            crawl_target: None,
-            total: None,
-        });
+    async fn send_abort_program_message(&self, target: &&String, why: ParseError) {
+        let reason = format!("Couldn't parse '{}' as a URL: {}", &target, why);
+        self.controller.fail(reason.clone());
+        self.console_sender
+            .send(ConsoleMessage {
+                message_type: ConsoleMessageType::Abort,
+                data: Err(reason),
+                original_target: None,
+                crawl_target: None,
+                total: None,
+            })
+            .await;
     }
 
-    fn send_target_found_message(&self, crawl_target: &mut CrawlTarget) {
-        let _ = self.console_sender.send(ConsoleMessage {
-            message_type: ConsoleMessageType::CrawlResult,
-            data: Ok(String::default()),
-            original_target: None,
-            crawl_target: Some(crawl_target.clone()),
-            total: None,
-        });
+    async fn send_target_found_message(&self, crawl_target: &mut CrawlTarget) {
+        self.console_sender
+            .send(ConsoleMessage {
+                message_type: ConsoleMessageType::CrawlResult,
+                data: Ok(String::default()),
+                original_target: self.parent.clone(),
+                crawl_target: Some(crawl_target.clone()),
+                total: None,
+            })
+            .await;
     }
 
-    fn find_new_urls(&self, visited: &Arc<Mutex<Vec<String>>>, crawl_target: CrawlTarget) {
+    async fn find_new_urls(
+        &self,
+        visited: &Arc<Mutex<HashSet<String>>>,
+        crawl_target: CrawlTarget,
+    ) {
         let mut ct = crawl_target;
 
-        let url = Url::parse(&ct.url).unwrap();
-        let result = self.client.send_head(&mut ct, RequestOptions::default());
+        self.progress.start();
+        let result = self
+            .client
+            .send_head(&mut ct, RequestOptions::default())
+            .await;
+        self.progress.complete();
+        self.send_crawl_progress_message().await;
 
         if let Ok(res) = result {
-            self.send_target_hit_message(visited, &mut ct, &res);
+            self.send_target_hit_message(visited, &mut ct, &res).await;
 
-            let content_type = res.headers().get(reqwest::header::CONTENT_TYPE).unwrap();
-            let content_type = content_type.to_str().unwrap_or_default();
-            if !content_type.contains("text/") {
+            // a redirect may have taken us somewhere we were never meant to crawl
+            let url = res.url().clone();
+            if !self.is_in_scope(&url) {
+                debug!(
+                    "Not crawling {}, it redirected out of scope to {}",
+                    self.target, url
+                );
                 return;
             }
 
-            match self
-                .client
-                .send_get(&mut ct, RequestOptions::with_partial_get())
-            {
-                Ok(res) => {
-                    if let Ok(body) = res.text() {
-                        let url_finder: Regex =
-                            Regex::new("(?:src=[\"']|href=[\"'])(/{0,2}[^\"',<>]*)").unwrap();
-                        url_finder
-                            .captures_iter(body.as_str())
-                            .for_each(|captures| match captures.get(1) {
-                                Some(u) => {
-                                    let part_url = &url.join(u.as_str()).unwrap();
-                                    if !visited.lock().unwrap().contains(&part_url.to_string()) {
-                                        let target_domain =
-                                            &part_url.domain().unwrap_or_default().to_string();
-                                        if !self.settings.scoped
-                                            || (self.settings.scoped
-                                                && self.scoped_domains.contains(target_domain))
-                                        {
-                                            self.recurse(&visited, part_url);
-                                        }
-                                    }
-                                }
-                                None => (),
-                            });
+            if self.settings.follow_sourcemaps && source_map::is_javascript(res.headers(), &url) {
+                self.follow_source_map(&mut ct, &url, res.headers()).await;
+                return;
+            }
+
+            let length = RinzlerCrawler::content_length(res.headers());
+            if !RinzlerCrawler::is_text(res.headers()) {
+                if self.settings.save_all {
+                    self.save_body(&mut ct, length).await;
+                }
+                return;
+            }
+
+            if let Some(links) = self.fetch_links(&mut ct, &url, length).await {
+                let (in_scope, out_of_scope): (Vec<Url>, Vec<Url>) = links
+                    .into_iter()
+                    .partition(|part_url| self.is_in_scope(part_url));
+                if self.settings.report_out_of_scope {
+                    for part_url in &out_of_scope {
+                        self.send_external_domain_message(&ct, part_url).await;
+                    }
+                }
+                if self.settings.graph_output.is_some() {
+                    for part_url in &in_scope {
+                        self.graph.add_edge(&ct.url, part_url.as_str());
+                    }
+                }
+                // a link is marked visited as it's queued, so pages still waiting aren't queued again
+                let new_urls: Vec<Url> = in_scope
+                    .into_iter()
+                    .filter(|part_url| self.is_in_path_scope(part_url))
+                    .filter(|part_url| !self.is_crawler_trap(part_url))
+                    .filter(|part_url| !self.has_excluded_extension(part_url))
+                    .filter(|part_url| {
+                        let normalized = CrawlTarget::normalize_url(part_url.as_str());
+                        visited.lock().unwrap().insert(normalized)
+                    })
+                    .collect();
+
+                for part_url in new_urls.iter() {
+                    self.recurse(&ct, part_url);
+                }
+                self.send_crawl_progress_message().await;
+            }
+        }
+    }
+
+    // the comment naming the map sits at the very end, so the whole script is read
+    async fn follow_source_map(&self, ct: &mut CrawlTarget, url: &Url, head: &HeaderMap) {
+        let res = match self.client.send_get(ct, RequestOptions::default()).await {
+            Ok(res) => res,
+            Err(_) => return,
+        };
+        let headers = res.headers().clone();
+        let content_type = headers
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .map(|content_type| content_type.to_string());
+        let bytes = match self.client.read_body(res).await {
+            Ok(bytes) => bytes,
+            Err(_) => return,
+        };
+        let body = charset::decode(content_type.as_deref(), &bytes);
+        let map_url = source_map::map_url(url, &headers, &body)
+            .or_else(|| source_map::map_url(url, head, ""));
+        let map_url = match map_url {
+            Some(map_url) if self.is_in_scope(&map_url) => map_url,
+            Some(map_url) => {
+                debug!("Not fetching {}, the source map is out of scope", map_url);
+                return;
+            }
+            None => return,
+        };
+
+        let mut map_target = CrawlTarget::new();
+        map_target.url = map_url.to_string();
+        let map = match self
+            .client
+            .send_get(&mut map_target, RequestOptions::default())
+            .await
+        {
+            Ok(res) if res.status().is_success() => self.client.read_body(res).await.ok(),
+            _ => None,
+        };
+        match map.and_then(|map| serde_json::from_slice::<Value>(&map).ok()) {
+            Some(map) => {
+                for source in source_map::sources(&map) {
+                    self.send_harvested_message(ConsoleMessageType::SourceMapSource, ct, &source)
+                        .await;
+                }
+            }
+            None => warn!("{} isn't a source map that could be read", map_url),
+        }
+    }
+
+    // reading the body is what saves it, and there are no links to look for
+    async fn save_body(&self, ct: &mut CrawlTarget, length: Option<u64>) {
+        if let Ok(res) = self.client.send_get(ct, RequestOptions::default()).await {
+            let _ = self.read_body_showing_progress(ct, res, length).await;
+        }
+    }
+
+    async fn fetch_links(
+        &self,
+        ct: &mut CrawlTarget,
+        url: &Url,
+        length: Option<u64>,
+    ) -> Option<Vec<Url>> {
+        let cached = self.cache.as_ref().and_then(|cache| cache.load(&ct.url));
+        let res = match &cached {
+            Some(entry) => self.client.send_conditional_get(ct, entry).await,
+            None => {
+                self.client
+                    .send_get(ct, RequestOptions::with_partial_get())
+                    .await
+            }
+        }
+        .ok()?;
+        self.check_redirect_loop(ct, &res).await;
+
+        if res.status() == StatusCode::NOT_MODIFIED {
+            debug!("{} hasn't changed, using cached links", ct.url);
+            return cached.map(|entry| entry.links);
+        }
+
+        let headers = res.headers().clone();
+        let (_, body) = self.read_preview(ct, res, length).await?;
+        let mut links = match self.duplicate_of(url, body.as_str()) {
+            // the canonical page has the same links, and is crawled once however many copies there are
+            Some(canonical) => {
+                debug!("{} is a copy of {}, crawling that instead", url, canonical);
+                vec![canonical]
+            }
+            None => self.link_finder.find(url, body.as_str()),
+        };
+        if self.settings.follow_meta_refresh {
+            if let Some(refresh) = RinzlerCrawler::find_meta_refresh(url, body.as_str()) {
+                debug!("{} refreshes to {}", url, refresh);
+                if !links.contains(&refresh) {
+                    links.push(refresh);
+                }
+            }
+        }
+        self.report_forms(ct, url, body.as_str()).await;
+        self.harvest(ct, body.as_str()).await;
+        if let Some(cache) = &self.cache {
+            cache.store(&ct.url, &headers, &links);
+        }
+        Some(links)
+    }
+
+    async fn extract_links(&self, crawl_target: CrawlTarget) {
+        let mut ct = crawl_target;
+        let url = Url::parse(&ct.url).unwrap();
+
+        if let Ok(res) = self
+            .client
+            .send_get(&mut ct, RequestOptions::with_partial_get())
+            .await
+        {
+            if let Some((_, body)) = self.read_preview(&ct, res, None).await {
+                for link in self.link_finder.find(&url, body.as_str()) {
+                    self.send_link_found_message(&ct, link).await;
+                }
+                self.report_forms(&ct, &url, body.as_str()).await;
+                self.harvest(&ct, body.as_str()).await;
+            }
+        }
+    }
+
+    async fn report_forms(&self, ct: &CrawlTarget, url: &Url, body: &str) {
+        if !self.settings.extract_forms {
+            return;
+        }
+        for form in HtmlForm::find_all(url, body) {
+            self.console_sender
+                .send(ConsoleMessage {
+                    message_type: ConsoleMessageType::FormFound,
+                    data: Ok(form.to_string()),
+                    original_target: None,
+                    crawl_target: Some(ct.clone()),
+                    total: None,
+                })
+                .await;
+        }
+    }
+
+    async fn harvest(&self, ct: &CrawlTarget, body: &str) {
+        if !self.settings.harvest {
+            return;
+        }
+        for email in EMAIL_FINDER.find_iter(body) {
+            self.send_harvested_message(ConsoleMessageType::HarvestedEmail, ct, email.as_str())
+                .await;
+        }
+        for captures in COMMENT_FINDER.captures_iter(body) {
+            let comment = captures[1]
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" ");
+            if !comment.is_empty() {
+                self.send_harvested_message(ConsoleMessageType::HarvestedComment, ct, &comment)
+                    .await;
+            }
+        }
+    }
+
+    async fn send_external_domain_message(&self, ct: &CrawlTarget, url: &Url) {
+        if let Some(host) = url.host_str() {
+            self.console_sender
+                .send(ConsoleMessage {
+                    message_type: ConsoleMessageType::ExternalDomain,
+                    data: Ok(host.to_string()),
+                    original_target: None,
+                    crawl_target: Some(ct.clone()),
+                    total: None,
+                })
+                .await;
+        }
+    }
+
+    async fn send_harvested_message(
+        &self,
+        message_type: ConsoleMessageType,
+        ct: &CrawlTarget,
+        item: &str,
+    ) {
+        self.console_sender
+            .send(ConsoleMessage {
+                message_type,
+                data: Ok(item.to_string()),
+                original_target: None,
+                crawl_target: Some(ct.clone()),
+                total: None,
+            })
+            .await;
+    }
+
+    // the response's own length wins over the one the HEAD gave, which is only needed for bodies
+    // sent in chunks. Progress is drawn once a body is known, or turns out, to be large
+    async fn read_body_showing_progress(
+        &self,
+        ct: &CrawlTarget,
+        res: Response,
+        length: Option<u64>,
+    ) -> Result<Vec<u8>> {
+        let partial = res.status() == StatusCode::PARTIAL_CONTENT;
+        let length = RinzlerCrawler::content_length(res.headers())
+            .or(match self.settings.preview_bytes {
+                preview_bytes if partial && preview_bytes > 0 => {
+                    length.map(|length| length.min(preview_bytes))
+                }
+                _ => length,
+            })
+            .map(|length| match self.settings.max_body_size {
+                0 => length,
+                max_body_size => length.min(max_body_size as u64),
+            });
+        let mut shown = false;
+        let mut reported = 0;
+        let body = self
+            .client
+            .read_body_reporting(res, |read| {
+                let read = read as u64;
+                let mut messages = vec![];
+                if !shown && length.unwrap_or_default().max(read) >= DOWNLOAD_PROGRESS_MIN {
+                    shown = true;
+                    messages.push((ConsoleMessageType::DownloadStart, length));
+                }
+                if shown && read - reported >= DOWNLOAD_PROGRESS_STEP {
+                    reported = read;
+                    messages.push((ConsoleMessageType::DownloadProgress, Some(read)));
+                }
+                async move {
+                    for (message_type, total) in messages {
+                        self.send_download_message(message_type, ct, total).await;
                     }
                 }
-                Err(_) => {}
+            })
+            .await;
+        if shown {
+            self.send_download_message(ConsoleMessageType::DownloadFinish, ct, None)
+                .await;
+        }
+        body
+    }
+
+    fn content_length(headers: &HeaderMap) -> Option<u64> {
+        headers
+            .get(reqwest::header::CONTENT_LENGTH)?
+            .to_str()
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    async fn send_download_message(
+        &self,
+        message_type: ConsoleMessageType,
+        ct: &CrawlTarget,
+        total: Option<u64>,
+    ) {
+        self.console_sender
+            .send(ConsoleMessage {
+                message_type,
+                data: Ok(String::default()),
+                original_target: None,
+                crawl_target: Some(ct.clone()),
+                total,
+            })
+            .await;
+    }
+
+    // a partial GET answers 206 where the HEAD said 200, and may stop half way through a tag
+    async fn read_preview(
+        &self,
+        ct: &CrawlTarget,
+        res: Response,
+        length: Option<u64>,
+    ) -> Option<(u16, String)> {
+        let partial = res.status() == StatusCode::PARTIAL_CONTENT;
+        let status_code = if partial {
+            StatusCode::OK.as_u16()
+        } else {
+            res.status().as_u16()
+        };
+
+        let content_type = res
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .map(|content_type| content_type.to_string());
+        let bytes = self
+            .read_body_showing_progress(ct, res, length)
+            .await
+            .ok()?;
+        let mut body = charset::decode(content_type.as_deref(), &bytes);
+        if partial {
+            if let Some(end) = body.rfind('>') {
+                body.truncate(end + 1);
             }
         }
+        Some((status_code, body))
+    }
+
+    // a canonical URL out of scope couldn't be crawled in place of the page, so it's ignored
+    fn duplicate_of(&self, url: &Url, body: &str) -> Option<Url> {
+        if !self.settings.respect_canonical {
+            return None;
+        }
+        let canonical = RinzlerCrawler::find_canonical(url, body)?;
+        let same = CrawlTarget::normalize_url(canonical.as_str())
+            == CrawlTarget::normalize_url(url.as_str());
+        (!same && self.is_in_scope(&canonical)).then_some(canonical)
+    }
+
+    fn find_canonical(url: &Url, body: &str) -> Option<Url> {
+        let tag = LINK_TAG_FINDER
+            .find_iter(body)
+            .map(|tag| tag.as_str())
+            .find(|tag| CANONICAL_REL.is_match(tag))?;
+        let captures = HREF_FINDER.captures(tag)?;
+        let href = captures
+            .get(1)
+            .or_else(|| captures.get(2))
+            .or_else(|| captures.get(3))?;
+        url.join(href.as_str().trim()).ok()
+    }
+
+    fn find_meta_refresh(url: &Url, body: &str) -> Option<Url> {
+        let tag = META_TAG_FINDER
+            .find_iter(body)
+            .map(|tag| tag.as_str())
+            .find(|tag| REFRESH_EQUIV.is_match(tag))?;
+        let captures = CONTENT_FINDER.captures(tag)?;
+        let content = captures
+            .get(1)
+            .or_else(|| captures.get(2))
+            .or_else(|| captures.get(3))?;
+        let target = REFRESH_URL
+            .captures(content.as_str())?
+            .get(1)?
+            .as_str()
+            .trim();
+        // a refresh without a url only reloads the page
+        if target.is_empty() {
+            return None;
+        }
+        url.join(target).ok()
+    }
+
+    // host_str rather than domain, which is None for IP address hosts
+    fn is_in_scope(&self, url: &Url) -> bool {
+        RinzlerCrawler::url_in_scope(&self.settings, &self.scoped_domains, url)
+    }
+
+    fn url_in_scope(settings: &RinzlerSettings, scoped_domains: &[String], url: &Url) -> bool {
+        let target_host = url.host_str().unwrap_or_default();
+        !settings.scoped
+            || scoped_domains.iter().any(|scoped| {
+                target_host == scoped
+                    || (settings.include_subdomains
+                        && url.domain().is_some()
+                        && target_host
+                            .strip_suffix(scoped.as_str())
+                            .is_some_and(|sub| sub.ends_with('.')))
+            })
+            || settings
+                .scope_regexes
+                .iter()
+                .any(|r| r.is_match(target_host))
+    }
+
+    fn is_in_path_scope(&self, url: &Url) -> bool {
+        let path_scopes = &self.settings.path_scopes;
+        path_scopes.is_empty()
+            || path_scopes
+                .iter()
+                .any(|prefix| url.path().starts_with(prefix))
+    }
+
+    async fn send_link_found_message(&self, page: &CrawlTarget, link: Url) {
+        let message_type = if self.is_in_scope(&link) {
+            ConsoleMessageType::InScopeLink
+        } else {
+            ConsoleMessageType::OutOfScopeLink
+        };
+        self.console_sender
+            .send(ConsoleMessage {
+                message_type,
+                data: Ok(String::default()),
+                original_target: Some(page.clone()),
+                crawl_target: Some(CrawlTarget::from_url(link)),
+                total: None,
+            })
+            .await;
     }
 
-    fn send_target_hit_message(
+    async fn send_crawl_progress_message(&self) {
+        self.console_sender
+            .send(ConsoleMessage {
+                message_type: ConsoleMessageType::CrawlProgress,
+                data: Ok(self.progress.summary()),
+                original_target: None,
+                crawl_target: None,
+                total: None,
+            })
+            .await;
+    }
+
+    async fn send_target_hit_message(
         &self,
-        visited: &Arc<Mutex<Vec<String>>>,
+        visited: &Arc<Mutex<HashSet<String>>>,
         ct: &mut CrawlTarget,
         res: &Response,
     ) {
-        visited.lock().unwrap().push(ct.url.clone());
+        visited
+            .lock()
+            .unwrap()
+            .insert(CrawlTarget::normalize_url(&ct.url));
         ct.url = res.url().to_string();
         ct.status_code = Some(u16::from(res.status()));
         ct.timestamp = Local::now();
+        // a page that redirected out of scope isn't a finding, so it doesn't count towards --fail-on
+        if self.is_allowed(res) && self.is_in_scope(res.url()) {
+            self.progress.found(ct.status_code);
+            self.notify_webhook(ct);
+        }
 
-        let _ = self.console_sender.send(ConsoleMessage {
-            message_type: ConsoleMessageType::CrawlResult,
-            data: Ok(String::default()),
-            original_target: None,
-            crawl_target: Some(ct.clone()),
-            total: None,
-        });
+        self.console_sender
+            .send(ConsoleMessage {
+                message_type: ConsoleMessageType::CrawlResult,
+                data: Ok(String::default()),
+                original_target: self.parent.clone(),
+                crawl_target: Some(ct.clone()),
+                total: None,
+            })
+            .await;
+    }
+
+    fn has_excluded_extension(&self, url: &Url) -> bool {
+        let extension = url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .and_then(|last| last.rsplit_once('.'))
+            .map(|(_, extension)| extension.to_lowercase());
+        match extension {
+            Some(extension) if self.settings.exclude_extensions.contains(&extension) => {
+                debug!("Not crawling {}, .{} is excluded", url, extension);
+                true
+            }
+            _ => false,
+        }
     }
 
-    fn recurse(&self, visited: &Arc<Mutex<Vec<String>>>, part_url: &Url) {
+    // broken relative links can make paths like /a/b/a/b/a/b/... that never stop growing
+    fn is_crawler_trap(&self, url: &Url) -> bool {
+        let segments: Vec<&str> = url
+            .path_segments()
+            .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        if segments.len() > self.settings.max_path_depth {
+            debug!("Not crawling {}, its path is too deep", url);
+            return true;
+        }
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for segment in &segments {
+            let count = counts.entry(segment).or_insert(0);
+            *count += 1;
+            if *count > self.settings.max_repeat {
+                debug!("Not crawling {}, '{}' repeats too often", url, segment);
+                return true;
+            }
+        }
+        false
+    }
+
+    pub(crate) fn target(&self) -> &str {
+        &self.target
+    }
+
+    fn recurse(&self, parent: &CrawlTarget, part_url: &Url) {
         let new_crawl = RinzlerCrawler {
             target: part_url.to_string(),
             settings: self.settings.clone(),
-            controller_sender: self.controller_sender.clone(),
+            controller: self.controller.clone(),
             console_sender: self.console_sender.clone(),
             scoped_domains: self.scoped_domains.clone(),
             client: self.client.clone(),
+            progress: Arc::clone(&self.progress),
+            graph: Arc::clone(&self.graph),
+            security_findings: Arc::clone(&self.security_findings),
+            webhook: self.webhook.clone(),
+            frontier: Arc::clone(&self.frontier),
+            skipped: Arc::clone(&self.skipped),
+            parent: Some(parent.clone()),
+            root: false,
+            cache: self.cache.clone(),
+            link_finder: Arc::clone(&self.link_finder),
         };
-        let _ = new_crawl.crawl(Arc::clone(&visited));
+        self.frontier.push(new_crawl);
     }
 
-    fn force_browse(
+    async fn force_browse(
         &self,
-        visited: &Arc<Mutex<Vec<String>>>,
+        visited: &Arc<Mutex<HashSet<String>>>,
         mut crawl_target: CrawlTarget,
-        wordlist: Vec<String>,
+        wordlist: &[String],
+        sensitive: bool,
     ) {
         crawl_target.method = "HEAD".to_string();
         if let Ok(base_url) = Url::parse(crawl_target.url.as_str()) {
-            self.send_start_force_browse_message(wordlist.len(), crawl_target.clone());
-            wordlist.par_iter().for_each(|word| {
-                if let Ok(to_visit) = base_url.join(word.as_str()) {
-                    let mut new_crawl_target = CrawlTarget::from_url(to_visit.clone());
-                    self.send_force_browse_attempt(new_crawl_target.clone(), crawl_target.clone());
-                    let result = self.send_head_or_get(&mut new_crawl_target);
-
-                    match result {
-                        Ok(response) => {
-                            let status_code = response.status();
-                            if self.is_allowed(u16::from(status_code)) {
-                                self.send_force_browse_hit(visited, crawl_target.clone(), &response)
+            let soft_404 = self.calibrate_soft_404(&base_url, sensitive).await;
+            let mutator = WordMutator::from_settings(&self.settings);
+            // the sensitive file list is exact paths, so it's never mutated
+            let mutate = !sensitive && !mutator.is_noop();
+            let total = match mutate {
+                true => wordlist
+                    .iter()
+                    .map(|word| mutator.variants(word).len())
+                    .sum(),
+                false => wordlist.len(),
+            };
+            self.send_start_force_browse_message(total, crawl_target.clone())
+                .await;
+            let (base_url, soft_404, crawl_target) = (&base_url, &soft_404, &crawl_target);
+            let words = wordlist.iter().flat_map(|word| match mutate {
+                true => mutator.variants(word),
+                false => vec![word.clone()],
+            });
+            stream::iter(words)
+                .for_each_concurrent(self.settings.max_threads, |word| async move {
+                    // the rest of the wordlist drains without sending anything once skipped
+                    if self.skipped.contains(crawl_target) {
+                        return;
+                    }
+                    let word = word.as_str();
+                    if let Some(request) = self.fuzz_request(base_url, word, sensitive) {
+                        // when only the body or headers are fuzzed every word has the same URL
+                        if request.url != *base_url
+                            && self
+                                .settings
+                                .seen
+                                .contains(&CrawlTarget::normalize_url(request.url.as_str()))
+                        {
+                            self.send_force_browse_progress(crawl_target.clone()).await;
+                            return;
+                        }
+                        let mut new_crawl_target = CrawlTarget::from_url(request.url.clone());
+                        self.send_force_browse_attempt(
+                            new_crawl_target.clone(),
+                            crawl_target.clone(),
+                        ).await;
+                        // dropping the request cancels it, whether it's queued on the throttle or in flight
+                        let result = tokio::select! {
+                            result = self.send_fuzz_request(&mut new_crawl_target, &request) => result,
+                            _ = self.skipped.skipped(crawl_target) => return,
+                        };
+                        let method = new_crawl_target.method.clone();
+
+                        match result {
+                            Ok(response) if sensitive => {
+                                if response.status() == StatusCode::OK
+                                    && (self.is_skipped_content(&response)
+                                        || !self
+                                            .is_soft_404(
+                                                soft_404,
+                                                200,
+                                                &mut new_crawl_target,
+                                                word,
+                                                &request,
+                                            )
+                                            .await)
+                                {
+                                    new_crawl_target.url = response.url().to_string();
+                                    new_crawl_target.status_code = Some(200);
+                                    self.report_security_finding(
+                                        &SENSITIVE_FILE,
+                                        format!("{} is exposed", word),
+                                        &new_crawl_target,
+                                    ).await;
+                                }
+                            }
+                            Ok(response)
+                                if mutate
+                                    && RinzlerCrawler::redirected_to_slash(
+                                        &request.url,
+                                        &response,
+                                    ) =>
+                            {
+                                debug!("{} is reported by its slashed variant", request.url);
                             }
+                            Ok(response) => {
+                                let status_code = response.status();
+                                let protected = self.is_protected(&response);
+                                if (protected || self.is_allowed(&response))
+                                    && (self.is_skipped_content(&response)
+                                        || !self
+                                            .is_soft_404(
+                                                soft_404,
+                                                u16::from(status_code),
+                                                &mut new_crawl_target,
+                                                word,
+                                                &request,
+                                            )
+                                            .await)
+                                {
+                                    let mut hit = crawl_target.clone();
+                                    hit.method = method;
+                                    hit.redirect_chain = new_crawl_target.redirect_chain.clone();
+                                    hit.response_time_ms = new_crawl_target.response_time_ms;
+                                    hit.content_encoding =
+                                        new_crawl_target.content_encoding.clone();
+                                    self.send_force_browse_hit(visited, hit, &response, protected)
+                                        .await;
+                                }
+                            }
+                            Err(_) => { /* probably nothing to do here */ }
                         }
-                        Err(_) => { /* probably nothing to do here */ }
+                        self.send_force_browse_progress(crawl_target.clone()).await;
                     }
-                    self.send_force_browse_progress(crawl_target.clone());
-                }
+                })
+                .await;
+        }
+    }
+
+    async fn calibrate_soft_404(
+        &self,
+        base_url: &Url,
+        sensitive: bool,
+    ) -> Option<Soft404Signature> {
+        // a fuzzed query parameter is compared against it being empty, which is always valid
+        let bogus = match !sensitive && self.fuzzes_query() {
+            true => String::new(),
+            false => Uuid::new_v4().to_string(),
+        };
+        let request = self.fuzz_request(base_url, &bogus, sensitive)?;
+        let mut ct = CrawlTarget::from_url(request.url.clone());
+        let (status_code, body) = self.fetch_fuzz_preview(&mut ct, &request).await?;
+        if status_code == 404 {
+            return None;
+        }
+
+        let signature = Soft404Signature::new(status_code, body.as_str(), &bogus);
+        info!("Soft 404 signature for {}: {}", base_url, signature);
+        Some(signature)
+    }
+
+    async fn is_soft_404(
+        &self,
+        soft_404: &Option<Soft404Signature>,
+        status_code: u16,
+        crawl_target: &mut CrawlTarget,
+        word: &str,
+        request: &FuzzRequest,
+    ) -> bool {
+        let signature = match soft_404 {
+            Some(signature) if signature.status_code() == status_code => signature,
+            _ => return false,
+        };
+
+        match self.fetch_fuzz_preview(crawl_target, request).await {
+            Some((status_code, body)) if signature.matches(status_code, body.as_str(), word) => {
+                debug!("Suppressing soft 404 at {}", crawl_target.url);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // a directory asked for without its slash usually redirects to it, so it'd be reported twice
+    fn redirected_to_slash(requested: &Url, res: &Response) -> bool {
+        !requested.path().ends_with('/')
+            && res.url().path() == format!("{}/", requested.path())
+            && res.url().host_str() == requested.host_str()
+    }
+
+    // Content-Type can carry parameters, e.g. text/html; charset=utf-8, so only its type is matched
+    // only the essence is parsed, as mime rejects the whitespace HTTP allows before parameters
+    fn is_text(headers: &HeaderMap) -> bool {
+        headers
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .and_then(|content_type| content_type.split(';').next())
+            .and_then(|essence| essence.trim().parse::<Mime>().ok())
+            .is_some_and(|mime| mime.type_() == mime::TEXT)
+    }
+
+    // checking for a soft 404 downloads the body, which isn't worth it for media and archives
+    fn is_skipped_content(&self, res: &Response) -> bool {
+        let content_type = match res.headers().get(reqwest::header::CONTENT_TYPE) {
+            Some(content_type) => content_type.to_str().unwrap_or_default().to_lowercase(),
+            None => return false,
+        };
+        let skipped = self
+            .settings
+            .skip_content_types
+            .iter()
+            .any(|skip| content_type.starts_with(skip.as_str()));
+        if skipped {
+            debug!("Not downloading {} ({})", res.url(), content_type);
+        }
+        skipped
+    }
+
+    // the sensitive file checks always probe paths with HEAD/GET, whatever the fuzzing options
+    fn fuzz_request(&self, base_url: &Url, word: &str, sensitive: bool) -> Option<FuzzRequest> {
+        let url =
+            RinzlerCrawler::word_url(&self.settings, &self.target, base_url, word, sensitive)?;
+        if sensitive {
+            return Some(FuzzRequest {
+                url,
+                method: None,
+                body: None,
+                headers: HeaderMap::new(),
             });
         }
+        Some(FuzzRequest {
+            url,
+            method: self.settings.method.clone(),
+            body: self
+                .settings
+                .data
+                .as_ref()
+                .map(|data| data.replace(FUZZ_KEYWORD, word)),
+            headers: RinzlerClient::header_map(
+                self.settings
+                    .headers
+                    .iter()
+                    .filter(|(name, value)| {
+                        name.contains(FUZZ_KEYWORD) || value.contains(FUZZ_KEYWORD)
+                    })
+                    .map(|(name, value)| {
+                        (
+                            name.replace(FUZZ_KEYWORD, word),
+                            value.replace(FUZZ_KEYWORD, word),
+                        )
+                    }),
+            ),
+        })
     }
 
-    fn send_head_or_get(&self, crawl_target: &mut CrawlTarget) -> Result<Response> {
-        let mut ct = crawl_target;
-        let result = self.client.send_head(&mut ct, RequestOptions::default());
+    fn word_url(
+        settings: &RinzlerSettings,
+        target: &str,
+        base_url: &Url,
+        word: &str,
+        sensitive: bool,
+    ) -> Option<Url> {
+        if !sensitive && settings.flags.contains(Flags::FUZZ) {
+            RinzlerCrawler::fuzz_url(target, word)
+        } else {
+            base_url.join(word).ok()
+        }
+    }
+
+    /// Every URL force browsing the original targets would request, without sending anything.
+    /// Soft 404 calibration, and the pages crawling and recursion turn up, can't be known ahead.
+    pub fn planned_urls(settings: &RinzlerSettings) -> Vec<Url> {
+        let mutator = WordMutator::from_settings(settings);
+        let mut urls = vec![];
+        for target in &settings.hosts {
+            let base_url = match Url::parse(target) {
+                Ok(base_url) => base_url,
+                Err(_) => continue,
+            };
+            let checks = COMMON_CHECKS
+                .iter()
+                .filter(|_| settings.common_checks)
+                .filter_map(|word| {
+                    RinzlerCrawler::word_url(settings, target, &base_url, word, true)
+                });
+            let words = settings
+                .wordlist
+                .iter()
+                .filter(|_| settings.flags.intersects(Flags::BRUTE | Flags::FUZZ))
+                .flat_map(|wordlist| wordlist.iter())
+                .flat_map(|word| mutator.variants(word))
+                .filter_map(|word| {
+                    RinzlerCrawler::word_url(settings, target, &base_url, &word, false)
+                })
+                .filter(|url| {
+                    *url == base_url
+                        || !settings
+                            .seen
+                            .contains(&CrawlTarget::normalize_url(url.as_str()))
+                });
+            let mut planned: Vec<Url> = checks.chain(words).collect();
+            urls.push(base_url);
+            urls.append(&mut planned);
+        }
+        urls
+    }
+
+    fn fuzzes_query(&self) -> bool {
+        self.settings.flags.contains(Flags::FUZZ)
+            && self
+                .target
+                .split_once('?')
+                .is_some_and(|(_, query)| query.contains(FUZZ_KEYWORD))
+    }
+
+    // words substituted into the query string are encoded, so one containing & or # can't add a
+    // parameter or cut the query short. The other parameters are kept as they were.
+    fn fuzz_url(target: &str, word: &str) -> Option<Url> {
+        let (rest, query) = match target.split_once('?') {
+            Some((rest, query)) if query.contains(FUZZ_KEYWORD) => (rest, query),
+            _ => return Url::parse(&target.replace(FUZZ_KEYWORD, word)).ok(),
+        };
+        let (query, fragment) = match query.split_once('#') {
+            Some((query, fragment)) => (query, Some(fragment)),
+            None => (query, None),
+        };
+        let query = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(
+                form_urlencoded::parse(query.as_bytes()).map(|(name, value)| {
+                    (
+                        name.replace(FUZZ_KEYWORD, word),
+                        value.replace(FUZZ_KEYWORD, word),
+                    )
+                }),
+            )
+            .finish();
+        let mut url = Url::parse(&rest.replace(FUZZ_KEYWORD, word)).ok()?;
+        url.set_query(Some(&query));
+        url.set_fragment(fragment);
+        Some(url)
+    }
+
+    async fn send_fuzz_request(
+        &self,
+        crawl_target: &mut CrawlTarget,
+        request: &FuzzRequest,
+    ) -> Result<Response> {
+        let res = match &request.method {
+            Some(method) => {
+                self.client
+                    .send(
+                        crawl_target,
+                        method.clone(),
+                        request.body.clone(),
+                        request.headers.clone(),
+                        RequestOptions::default(),
+                    )
+                    .await
+            }
+            None => {
+                self.send_head_or_get(crawl_target, request.headers.clone())
+                    .await
+            }
+        }?;
+        self.check_redirect_loop(crawl_target, &res).await;
+        Ok(res)
+    }
+
+    async fn fetch_fuzz_preview(
+        &self,
+        crawl_target: &mut CrawlTarget,
+        request: &FuzzRequest,
+    ) -> Option<(u16, String)> {
+        let res = self
+            .client
+            .send(
+                crawl_target,
+                request.method.clone().unwrap_or(Method::GET),
+                request.body.clone(),
+                request.headers.clone(),
+                RequestOptions::with_partial_get(),
+            )
+            .await
+            .ok()?;
+        self.read_preview(crawl_target, res, None).await
+    }
+
+    async fn send_head_or_get(
+        &self,
+        crawl_target: &mut CrawlTarget,
+        headers: HeaderMap,
+    ) -> Result<Response> {
+        let ct = crawl_target;
+        let result = self
+            .client
+            .send(
+                ct,
+                Method::HEAD,
+                None,
+                headers.clone(),
+                RequestOptions::default(),
+            )
+            .await;
 
         match result {
             Ok(r) => match r.status().as_u16() {
-                500..=599 => self
-                    .client
-                    .send_get(&mut ct, RequestOptions::with_partial_get()),
+                500..=599 => {
+                    self.client
+                        .send(
+                            ct,
+                            Method::GET,
+                            None,
+                            headers,
+                            RequestOptions::with_partial_get(),
+                        )
+                        .await
+                }
                 _ => Ok(r),
             },
             Err(_) => result,
         }
     }
 
-    fn is_allowed(&self, code: u16) -> bool {
-        let allowed_status_codes = self.settings.status_include.to_owned();
-        let blocked_status_codes = self.settings.status_exclude.to_owned();
+    // a word that needs credentials still exists, whatever the filters say
+    fn is_protected(&self, res: &Response) -> bool {
+        self.settings.note_protected
+            && matches!(
+                res.status(),
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+            )
+    }
+
+    fn is_allowed(&self, res: &Response) -> bool {
+        let code = res.status().as_u16();
+        let allowed_status_codes = &self.settings.status_include;
+        let blocked_status_codes = &self.settings.status_exclude;
 
-        let inclusions_exist = allowed_status_codes.is_empty() == false;
-        let exclusions_exist = blocked_status_codes.is_empty() == false;
-        let code = &u16::from(code);
+        let inclusions_exist = !allowed_status_codes.is_empty();
+        let exclusions_exist = !blocked_status_codes.is_empty();
         let mut allow = true;
 
         if inclusions_exist {
-            allow &= allowed_status_codes.contains(code);
+            allow &= allowed_status_codes.iter().any(|m| m.matches(code));
         }
 
         if exclusions_exist {
-            allow &= !blocked_status_codes.contains(code);
+            allow &= !blocked_status_codes.iter().any(|m| m.matches(code));
+        }
+
+        let headers = res.headers();
+        if !self.settings.header_include.is_empty() {
+            allow &= self
+                .settings
+                .header_include
+                .iter()
+                .any(|m| m.matches(headers));
         }
+        allow &= !self
+            .settings
+            .header_exclude
+            .iter()
+            .any(|m| m.matches(headers));
 
         allow
     }
-    fn send_start_force_browse_message(&self, len: usize, ct: CrawlTarget) {
-        let _ = self.console_sender.send(ConsoleMessage {
-            message_type: ConsoleMessageType::ForceBrowseStart,
-            data: Ok(String::default()),
-            original_target: None,
-            crawl_target: Some(ct),
-            total: Some(len as u64),
-        });
+    async fn send_start_force_browse_message(&self, len: usize, ct: CrawlTarget) {
+        self.console_sender
+            .send(ConsoleMessage {
+                message_type: ConsoleMessageType::ForceBrowseStart,
+                data: Ok(String::default()),
+                original_target: None,
+                crawl_target: Some(ct),
+                total: Some(len as u64),
+            })
+            .await;
     }
-    fn send_force_browse_progress(&self, ct: CrawlTarget) {
-        let _ = self.console_sender.send(ConsoleMessage {
-            message_type: ConsoleMessageType::ForceBrowseProgress,
-            data: Ok(String::default()),
-            original_target: None,
-            crawl_target: Some(ct),
-            total: None,
-        });
+    async fn send_force_browse_progress(&self, ct: CrawlTarget) {
+        self.console_sender
+            .send(ConsoleMessage {
+                message_type: ConsoleMessageType::ForceBrowseProgress,
+                data: Ok(String::default()),
+                original_target: None,
+                crawl_target: Some(ct),
+                total: None,
+            })
+            .await;
     }
-    fn send_force_browse_hit(
+    async fn send_force_browse_hit(
         &self,
-        visited: &Arc<Mutex<Vec<String>>>,
+        visited: &Arc<Mutex<HashSet<String>>>,
         mut ct: CrawlTarget,
         response: &Response,
+        protected: bool,
     ) {
-        visited.lock().unwrap().push(ct.url.to_string());
+        visited
+            .lock()
+            .unwrap()
+            .insert(CrawlTarget::normalize_url(&ct.url));
         ct.url = response.url().to_string();
         ct.status_code = Some(u16::from(response.status()));
         ct.timestamp = Local::now();
+        self.progress.found(ct.status_code);
+        self.notify_webhook(&ct);
 
-        let _ = self.console_sender.send(ConsoleMessage {
-            message_type: ConsoleMessageType::ForceBrowseHit,
-            data: Ok(String::default()),
-            original_target: None,
-            crawl_target: Some(ct.clone()),
-            total: None,
-        });
+        self.console_sender
+            .send(ConsoleMessage {
+                message_type: match protected {
+                    true => ConsoleMessageType::ForceBrowseProtected,
+                    false => ConsoleMessageType::ForceBrowseHit,
+                },
+                data: Ok(String::default()),
+                original_target: None,
+                crawl_target: Some(ct.clone()),
+                total: None,
+            })
+            .await;
+    }
+    async fn report_tls_info(&self, crawl_target: &CrawlTarget) {
+        let url = match Url::parse(&crawl_target.url) {
+            Ok(url) => url,
+            Err(_) => return,
+        };
+        match TlsInfo::fetch(&url).await {
+            Ok(tls_info) => {
+                if tls_info.is_expired() {
+                    self.report_security_finding(
+                        &EXPIRED_CERTIFICATE,
+                        format!(
+                            "certificate expired on {}",
+                            tls_info.not_after.format("%Y-%m-%d")
+                        ),
+                        crawl_target,
+                    )
+                    .await;
+                }
+                self.console_sender
+                    .send(ConsoleMessage {
+                        message_type: ConsoleMessageType::TlsInfo,
+                        data: if tls_info.expires_soon() {
+                            Err(tls_info.to_string())
+                        } else {
+                            Ok(tls_info.to_string())
+                        },
+                        original_target: None,
+                        crawl_target: Some(crawl_target.clone()),
+                        total: None,
+                    })
+                    .await;
+            }
+            Err(why) => warn!("Couldn't read the TLS certificate of {}: {}", url, why),
+        }
+    }
+
+    async fn load_openapi(&self, source: &str) -> std::result::Result<Value, String> {
+        let spec = match Url::parse(source) {
+            Ok(url) if url.scheme().starts_with("http") => {
+                let mut ct = CrawlTarget::from_url(url);
+                let res = self
+                    .client
+                    .send_get(&mut ct, RequestOptions::default())
+                    .await
+                    .map_err(|why| why.to_string())?;
+                let body = self
+                    .client
+                    .read_body(res)
+                    .await
+                    .map_err(|why| why.to_string())?;
+                String::from_utf8_lossy(&body).into_owned()
+            }
+            _ => tokio::fs::read_to_string(source)
+                .await
+                .map_err(|why| why.to_string())?,
+        };
+        openapi::parse(&spec)
+    }
+
+    // requests every documented operation once, with placeholders for its path parameters
+    async fn probe_openapi(&self, source: &str, crawl_target: &CrawlTarget) {
+        let base_url = match Url::parse(&crawl_target.url) {
+            Ok(url) => url,
+            Err(_) => return,
+        };
+        let endpoints = match self.load_openapi(source).await {
+            Ok(spec) => openapi::endpoints(&spec),
+            Err(why) => {
+                warn!("OpenAPI spec '{}' couldn't be loaded: {}", source, why);
+                return;
+            }
+        };
+        info!(
+            "Probing {} documented operations on {}",
+            endpoints.len(),
+            base_url
+        );
+        let base_url = &base_url;
+        stream::iter(endpoints)
+            .for_each_concurrent(self.settings.max_threads, |endpoint| async move {
+                let url = match base_url.join(&endpoint.path) {
+                    Ok(url) => url,
+                    Err(_) => return,
+                };
+                let mut ct = CrawlTarget::from_url(url);
+                let res = self
+                    .client
+                    .send(
+                        &mut ct,
+                        endpoint.method.clone(),
+                        None,
+                        HeaderMap::new(),
+                        RequestOptions::default(),
+                    )
+                    .await;
+                match res {
+                    Ok(res) => self.report_api_endpoint(&endpoint, &mut ct, &res).await,
+                    Err(why) => debug!("{} failed: {}", endpoint, why),
+                }
+            })
+            .await;
+    }
+
+    async fn report_api_endpoint(
+        &self,
+        endpoint: &ApiEndpoint,
+        ct: &mut CrawlTarget,
+        res: &Response,
+    ) {
+        let status_code = res.status().as_u16();
+        ct.url = res.url().to_string();
+        ct.status_code = Some(status_code);
+        ct.timestamp = Local::now();
+        let data = if endpoint.is_expected(status_code) {
+            Ok(format!("{} {} {}", endpoint.method, ct.url, status_code))
+        } else {
+            self.progress.found(ct.status_code);
+            self.notify_webhook(ct);
+            Err(format!(
+                "{} {} {}, documented {}",
+                endpoint.method,
+                ct.url,
+                status_code,
+                endpoint.documented()
+            ))
+        };
+        self.console_sender
+            .send(ConsoleMessage {
+                message_type: ConsoleMessageType::ApiEndpoint,
+                data,
+                original_target: None,
+                crawl_target: Some(ct.clone()),
+                total: None,
+            })
+            .await;
     }
-    fn send_force_browse_attempt(&self, new_crawl_target: CrawlTarget, crawl_target: CrawlTarget) {
-        let _ = self.console_sender.send(ConsoleMessage {
-            message_type: ConsoleMessageType::ForceBrowseAttempt,
-            data: Ok(String::default()),
-            original_target: Some(crawl_target.clone()),
-            crawl_target: Some(new_crawl_target.clone()),
-            total: None,
+
+    // swaps each redirect-looking parameter for a canary and sees whether the server sends us there
+    async fn check_open_redirects(&self, crawl_target: &CrawlTarget) {
+        let url = match Url::parse(&crawl_target.url) {
+            Ok(url) => url,
+            Err(_) => return,
+        };
+        let params: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        for (name, _) in &params {
+            if !REDIRECT_PARAMS.contains(&name.to_lowercase().as_str()) {
+                continue;
+            }
+
+            let mut probe = url.clone();
+            probe
+                .query_pairs_mut()
+                .clear()
+                .extend_pairs(params.iter().map(|(k, v)| {
+                    if k == name {
+                        (k.as_str(), REDIRECT_CANARY)
+                    } else {
+                        (k.as_str(), v.as_str())
+                    }
+                }));
+            let mut ct = CrawlTarget::from_url(probe);
+            if let Ok(res) = self
+                .client
+                .send_head(&mut ct, RequestOptions::without_redirects())
+                .await
+            {
+                let canary = Url::parse(REDIRECT_CANARY).unwrap();
+                let location = RinzlerClient::redirect_location(&res);
+                if location.is_some_and(|l| l.host_str() == canary.host_str()) {
+                    ct.status_code = Some(res.status().as_u16());
+                    self.report_security_finding(
+                        &OPEN_REDIRECT,
+                        format!("'{}' parameter redirects anywhere", name),
+                        &ct,
+                    )
+                    .await;
+                }
+            }
+        }
+    }
+
+    // what Allow advertises is only reported, sending PUT or DELETE to find out could do damage
+    async fn check_methods(&self, crawl_target: &CrawlTarget) {
+        let mut ct = crawl_target.clone();
+        let allowed: Vec<String> = match self
+            .client
+            .send_options(&mut ct, RequestOptions::without_redirects())
+            .await
+        {
+            Ok(res) => {
+                ct.status_code = Some(res.status().as_u16());
+                // IIS lists what the whole server supports under Public
+                res.headers()
+                    .get_all(reqwest::header::ALLOW)
+                    .iter()
+                    .chain(res.headers().get_all("public").iter())
+                    .filter_map(|value| value.to_str().ok())
+                    .flat_map(|value| value.split(','))
+                    .map(|method| method.trim().to_uppercase())
+                    .filter(|method| !method.is_empty())
+                    .collect()
+            }
+            Err(_) => vec![],
+        };
+        let dangerous: Vec<&str> = DANGEROUS_METHOD_NAMES
+            .iter()
+            .copied()
+            .filter(|method| allowed.iter().any(|a| a == method))
+            .collect();
+        if !dangerous.is_empty() {
+            self.report_security_finding(
+                &DANGEROUS_METHODS,
+                format!("advertises {}", dangerous.join(", ")),
+                &ct,
+            )
+            .await;
+        }
+
+        for method in [Method::TRACE, Method::from_bytes(b"TRACK").unwrap()] {
+            let marker = Uuid::new_v4().to_string();
+            let mut trace = crawl_target.clone();
+            let echoed = match self
+                .client
+                .send_trace(&mut trace, method.clone(), &marker)
+                .await
+            {
+                Ok(res) if res.status().is_success() => {
+                    trace.status_code = Some(res.status().as_u16());
+                    let body = self.client.read_body(res).await.unwrap_or_default();
+                    String::from_utf8_lossy(&body).contains(&marker)
+                }
+                _ => false,
+            };
+            if echoed {
+                self.report_security_finding(
+                    &TRACE_ENABLED,
+                    format!("{} echoes the request back", method),
+                    &trace,
+                )
+                .await;
+            } else if allowed.contains(&method.to_string()) {
+                debug!("{} advertises {} but didn't echo it", ct.url, method);
+            }
+        }
+    }
+
+    // every URL that redirects into the same loop would otherwise be its own finding
+    async fn check_redirect_loop(&self, ct: &CrawlTarget, res: &Response) {
+        let next = match RinzlerClient::redirect_loop(ct, res) {
+            Some(next) => next,
+            None => return,
+        };
+        if !self
+            .security_findings
+            .first_seen(format!("{}{}", REDIRECT_LOOP.id, next))
+        {
+            return;
+        }
+        let mut hops: Vec<String> = ct
+            .redirect_chain
+            .iter()
+            .map(|(_, hop)| hop.clone())
+            .collect();
+        hops.push(res.url().to_string());
+        hops.push(next.to_string());
+        let mut ct = ct.clone();
+        ct.status_code = Some(res.status().as_u16());
+        self.report_security_finding(
+            &REDIRECT_LOOP,
+            format!("redirects in a loop: {}", hops.join(" -> ")),
+            &ct,
+        )
+        .await;
+    }
+
+    async fn report_security_finding(
+        &self,
+        rule: &'static SecurityRule,
+        message: String,
+        ct: &CrawlTarget,
+    ) {
+        self.progress.found(ct.status_code);
+        self.notify_webhook(ct);
+        self.security_findings.record(SecurityFinding {
+            rule,
+            message: message.clone(),
+            target: ct.clone(),
         });
+        self.console_sender
+            .send(ConsoleMessage {
+                message_type: ConsoleMessageType::SecurityFinding,
+                data: Ok(message),
+                original_target: None,
+                crawl_target: Some(ct.clone()),
+                total: None,
+            })
+            .await;
+    }
+    fn notify_webhook(&self, finding: &CrawlTarget) {
+        if let Some(webhook) = &self.webhook {
+            webhook.notify(finding);
+        }
+    }
+    async fn send_force_browse_attempt(
+        &self,
+        new_crawl_target: CrawlTarget,
+        crawl_target: CrawlTarget,
+    ) {
+        self.console_sender
+            .send(ConsoleMessage {
+                message_type: ConsoleMessageType::ForceBrowseAttempt,
+                data: Ok(String::default()),
+                original_target: Some(crawl_target.clone()),
+                crawl_target: Some(new_crawl_target.clone()),
+                total: None,
+            })
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crawler::charset;
+    use reqwest::header::{HeaderValue, CONTENT_TYPE};
+
+    fn with_content_type(content_type: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str(content_type).unwrap());
+        headers
+    }
+
+    fn in_scope(settings: &RinzlerSettings, url: &str) -> bool {
+        let scoped_domains = vec!["example.com".to_string(), "[::1]".to_string()];
+        RinzlerCrawler::url_in_scope(settings, &scoped_domains, &Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn html_with_a_charset_is_text() {
+        assert!(RinzlerCrawler::is_text(&with_content_type(
+            "text/html; charset=utf-8"
+        )));
+        assert!(RinzlerCrawler::is_text(&with_content_type(
+            "Text/HTML ; charset=\"utf-8\""
+        )));
+    }
+
+    #[test]
+    fn json_is_not_text() {
+        assert!(!RinzlerCrawler::is_text(&with_content_type(
+            "application/json"
+        )));
+        assert!(!RinzlerCrawler::is_text(&with_content_type(
+            "application/json; charset=utf-8"
+        )));
+        assert!(!RinzlerCrawler::is_text(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn decodes_by_the_charset_parameter() {
+        let body = "<a href=\"/résumé\">".as_bytes();
+        assert_eq!(
+            charset::decode(Some("text/html; charset=utf-8"), body),
+            "<a href=\"/résumé\">"
+        );
+        // json names no charset, so it's read as UTF-8
+        assert_eq!(
+            charset::decode(Some("application/json"), "{\"é\": 1}".as_bytes()),
+            "{\"é\": 1}"
+        );
+    }
+
+    #[test]
+    fn scope_is_the_targets_hosts() {
+        let settings = RinzlerSettings::default();
+        assert!(in_scope(&settings, "https://example.com/a"));
+        assert!(in_scope(&settings, "http://[::1]:8080/a"));
+        assert!(!in_scope(&settings, "https://www.example.com/a"));
+        assert!(!in_scope(&settings, "https://notexample.com/a"));
+        assert!(!in_scope(&settings, "http://127.0.0.1/a"));
+    }
+
+    #[test]
+    fn subdomains_are_in_scope_when_included() {
+        let settings = RinzlerSettings {
+            include_subdomains: true,
+            ..RinzlerSettings::default()
+        };
+        assert!(in_scope(&settings, "https://www.example.com/a"));
+        assert!(!in_scope(&settings, "https://notexample.com/a"));
+    }
+
+    #[test]
+    fn everything_is_in_scope_unscoped() {
+        let settings = RinzlerSettings {
+            scoped: false,
+            ..RinzlerSettings::default()
+        };
+        assert!(in_scope(&settings, "https://elsewhere.org/a"));
     }
 }
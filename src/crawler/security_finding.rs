@@ -0,0 +1,86 @@
+use crate::crawler::crawl_target::CrawlTarget;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+pub struct SecurityRule {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub level: &'static str,
+}
+
+pub static SENSITIVE_FILE: SecurityRule = SecurityRule {
+    id: "RNZ001",
+    name: "Sensitive file exposed",
+    description:
+        "A file that commonly holds secrets, source code or configuration is publicly reachable.",
+    level: "error",
+};
+
+pub static OPEN_REDIRECT: SecurityRule = SecurityRule {
+    id: "RNZ002",
+    name: "Open redirect",
+    description: "A URL parameter controls where the page redirects to, so a link on a trusted domain can send visitors anywhere.",
+    level: "warning",
+};
+
+pub static EXPIRED_CERTIFICATE: SecurityRule = SecurityRule {
+    id: "RNZ003",
+    name: "Expired TLS certificate",
+    description: "The host presents a TLS certificate that has expired, so browsers will refuse or warn about the connection.",
+    level: "error",
+};
+
+pub static REDIRECT_LOOP: SecurityRule = SecurityRule {
+    id: "RNZ004",
+    name: "Redirect loop",
+    description: "The server redirects back to a URL it already redirected from, so browsers give up with a too many redirects error.",
+    level: "warning",
+};
+
+pub static TRACE_ENABLED: SecurityRule = SecurityRule {
+    id: "RNZ005",
+    name: "TRACE enabled",
+    description: "The server echoes TRACE or TRACK requests back, headers included, which cross-site tracing uses to read cookies that scripts aren't allowed to see.",
+    level: "warning",
+};
+
+pub static DANGEROUS_METHODS: SecurityRule = SecurityRule {
+    id: "RNZ006",
+    name: "Dangerous HTTP methods allowed",
+    description: "The server's Allow header advertises methods that change content, like PUT or DELETE, which may let anyone modify the site.",
+    level: "note",
+};
+
+pub struct SecurityFinding {
+    pub rule: &'static SecurityRule,
+    pub message: String,
+    pub target: CrawlTarget,
+}
+
+pub struct SecurityFindings {
+    findings: Mutex<Vec<SecurityFinding>>,
+    seen: Mutex<HashSet<String>>,
+}
+
+impl SecurityFindings {
+    pub fn new() -> SecurityFindings {
+        SecurityFindings {
+            findings: Mutex::new(vec![]),
+            seen: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn record(&self, finding: SecurityFinding) {
+        self.findings.lock().unwrap().push(finding);
+    }
+
+    /// False once `key` has been seen, for findings that many URLs lead to the same cause of.
+    pub fn first_seen(&self, key: String) -> bool {
+        self.seen.lock().unwrap().insert(key)
+    }
+
+    pub fn take(&self) -> Vec<SecurityFinding> {
+        std::mem::take(&mut *self.findings.lock().unwrap())
+    }
+}
@@ -0,0 +1,40 @@
+use crate::crawler::crawl_target::CrawlTarget;
+use std::collections::HashSet;
+use std::sync::RwLock;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+/// Targets skipped from the console, which force browsing checks before each request.
+pub struct SkippedTargets {
+    ids: RwLock<HashSet<Uuid>>,
+    changed: Notify,
+}
+
+impl SkippedTargets {
+    pub fn new() -> SkippedTargets {
+        SkippedTargets {
+            ids: RwLock::new(HashSet::new()),
+            changed: Notify::new(),
+        }
+    }
+
+    pub fn skip(&self, crawl_target: &CrawlTarget) {
+        self.ids.write().unwrap().insert(crawl_target.id);
+        self.changed.notify_waiters();
+    }
+
+    pub fn contains(&self, crawl_target: &CrawlTarget) -> bool {
+        self.ids.read().unwrap().contains(&crawl_target.id)
+    }
+
+    /// Resolves once the target is skipped, so requests still queued for it can be dropped.
+    pub async fn skipped(&self, crawl_target: &CrawlTarget) {
+        loop {
+            let changed = self.changed.notified();
+            if self.contains(crawl_target) {
+                return;
+            }
+            changed.await;
+        }
+    }
+}
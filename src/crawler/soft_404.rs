@@ -0,0 +1,57 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+pub struct Soft404Signature {
+    status_code: u16,
+    body_len: usize,
+    body_hash: u64,
+}
+
+impl Soft404Signature {
+    pub fn new(status_code: u16, body: &str, requested: &str) -> Soft404Signature {
+        let body = Soft404Signature::normalize(body, requested);
+        Soft404Signature {
+            status_code,
+            body_len: body.len(),
+            body_hash: Soft404Signature::hash(&body),
+        }
+    }
+
+    pub fn status_code(&self) -> u16 {
+        self.status_code
+    }
+
+    pub fn matches(&self, status_code: u16, body: &str, requested: &str) -> bool {
+        if status_code != self.status_code {
+            return false;
+        }
+        let body = Soft404Signature::normalize(body, requested);
+        body.len() == self.body_len && Soft404Signature::hash(&body) == self.body_hash
+    }
+
+    // "not found" pages often echo the requested path back, so strip it before comparing
+    fn normalize(body: &str, requested: &str) -> String {
+        if requested.is_empty() {
+            body.to_string()
+        } else {
+            body.replace(requested, "")
+        }
+    }
+
+    fn hash(body: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl Display for Soft404Signature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "status {}, {} bytes, hash {:016x}",
+            self.status_code, self.body_len, self.body_hash
+        )
+    }
+}
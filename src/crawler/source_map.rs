@@ -0,0 +1,71 @@
+use mime::Mime;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::header::HeaderMap;
+use serde_json::Value;
+use url::Url;
+
+static SOURCE_MAPPING_URL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^\s*//[#@]\s*sourceMappingURL=(\S+)\s*$").unwrap());
+
+pub fn is_javascript(headers: &HeaderMap, url: &Url) -> bool {
+    let by_content_type = headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .and_then(|content_type| content_type.parse::<Mime>().ok())
+        .is_some_and(|mime| {
+            let subtype = mime.subtype().as_str().to_lowercase();
+            subtype.ends_with("javascript") || subtype.ends_with("ecmascript")
+        });
+    by_content_type || url.path().to_lowercase().ends_with(".js")
+}
+
+// the header wins over the comment, as it does in browsers
+pub fn map_url(js_url: &Url, headers: &HeaderMap, body: &str) -> Option<Url> {
+    let named = headers
+        .get("SourceMap")
+        .or_else(|| headers.get("X-SourceMap"))
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .or_else(|| {
+            SOURCE_MAPPING_URL
+                .captures_iter(body)
+                .last()
+                .map(|captures| captures[1].to_string())
+        })?;
+    // inline maps have nothing further to fetch
+    if named.starts_with("data:") {
+        return None;
+    }
+    js_url.join(&named).ok()
+}
+
+pub fn sources(map: &Value) -> Vec<String> {
+    let root = map
+        .get("sourceRoot")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let mut sources: Vec<String> = vec![];
+    let listed = map.get("sources").and_then(Value::as_array);
+    // index maps keep their sources inside each section
+    let sections = map.get("sections").and_then(Value::as_array);
+    for source in listed.into_iter().flatten().filter_map(Value::as_str) {
+        sources.push(with_root(root, source));
+    }
+    for section in sections.into_iter().flatten() {
+        if let Some(inner) = section.get("map") {
+            sources.extend(self::sources(inner));
+        }
+    }
+    sources
+}
+
+fn with_root(root: &str, source: &str) -> String {
+    if root.is_empty() || source.contains("://") {
+        source.to_string()
+    } else if root.ends_with('/') {
+        format!("{}{}", root, source)
+    } else {
+        format!("{}/{}", root, source)
+    }
+}
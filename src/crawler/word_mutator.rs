@@ -0,0 +1,95 @@
+use crate::config::{Mutation, RinzlerSettings, EXT_KEYWORD};
+
+pub struct WordMutator {
+    mutations: Vec<Mutation>,
+    prefixes: Vec<String>,
+    suffixes: Vec<String>,
+    extensions: Vec<String>,
+}
+
+impl WordMutator {
+    pub fn from_settings(settings: &RinzlerSettings) -> WordMutator {
+        WordMutator {
+            mutations: settings.mutations.clone(),
+            prefixes: settings.prefixes.clone(),
+            suffixes: settings.suffixes.clone(),
+            extensions: settings.extensions.clone(),
+        }
+    }
+
+    pub fn is_noop(&self) -> bool {
+        self.mutations.is_empty()
+            && self.prefixes.is_empty()
+            && self.suffixes.is_empty()
+            && self.extensions.is_empty()
+    }
+
+    // each word's variants are made as it's tried rather than expanding the whole wordlist up front
+    pub fn variants(&self, word: &str) -> Vec<String> {
+        let mut cased = vec![word.to_string()];
+        if self.mutations.contains(&Mutation::Upper) {
+            cased.push(word.to_uppercase());
+        }
+        if self.mutations.contains(&Mutation::Capitalize) {
+            cased.push(WordMutator::capitalize(word));
+        }
+
+        let mut variants: Vec<String> = vec![];
+        for base in cased.iter().flat_map(|word| self.with_extensions(word)) {
+            let affixed = std::iter::once(base.clone())
+                .chain(
+                    self.prefixes
+                        .iter()
+                        .map(|prefix| format!("{}{}", prefix, base)),
+                )
+                .chain(
+                    self.suffixes
+                        .iter()
+                        .map(|suffix| format!("{}{}", base, suffix)),
+                );
+            for variant in affixed {
+                let slashed = format!("{}/", variant.trim_end_matches('/'));
+                WordMutator::push_unique(&mut variants, variant);
+                if self.mutations.contains(&Mutation::Slash) {
+                    WordMutator::push_unique(&mut variants, slashed);
+                }
+            }
+        }
+        variants
+    }
+
+    // a word marking where its extension goes is only tried with one, others are also tried bare
+    fn with_extensions(&self, word: &str) -> Vec<String> {
+        if self.extensions.is_empty() {
+            return vec![word.to_string()];
+        }
+        match word.contains(EXT_KEYWORD) {
+            true => self
+                .extensions
+                .iter()
+                .map(|extension| word.replace(EXT_KEYWORD, extension))
+                .collect(),
+            false => std::iter::once(word.to_string())
+                .chain(
+                    self.extensions
+                        .iter()
+                        .map(|extension| format!("{}.{}", word, extension)),
+                )
+                .collect(),
+        }
+    }
+
+    fn capitalize(word: &str) -> String {
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::default(),
+        }
+    }
+
+    fn push_unique(variants: &mut Vec<String>, variant: String) {
+        if !variants.contains(&variant) {
+            variants.push(variant);
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use crate::app::{RinzlerApplication, CONSOLE_BACKLOG};
+use crate::config::{CrawlOrder, RinzlerSettings};
+use crate::crawler::crawl_target::CrawlTarget;
+use crate::crawler::request_errors::RequestError;
+use crate::ui::rinzler_console::{ConsoleMessage, ConsoleMessageType};
+use crossbeam::channel::{bounded, RecvTimeoutError};
+use futures::channel::mpsc;
+use futures::future;
+use futures::{Stream, StreamExt};
+use std::sync::Arc;
+use std::time::Duration;
+
+// failed requests aren't sent as messages, so they're checked for this often
+const ERROR_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+type ResultHandler = Arc<dyn Fn(&CrawlTarget) + Send + Sync>;
+
+/// What a scan reports as it goes, in the order it happens.
+pub enum ScanEvent {
+    /// A page reached by crawling.
+    Found(CrawlTarget),
+    /// A page force browsing turned up.
+    Hit(CrawlTarget),
+    /// A request that got no response at all.
+    Error(RequestError),
+    /// The scan is over, and why. Always the last event.
+    Finished(Result<String, String>),
+}
+
+impl ScanEvent {
+    // the same pages the console lists and --output-json writes
+    fn from_message(message: ConsoleMessage) -> Option<ScanEvent> {
+        match message.message_type {
+            ConsoleMessageType::ForceBrowseHit | ConsoleMessageType::ForceBrowseProtected => {
+                message.crawl_target.map(ScanEvent::Hit)
+            }
+            ConsoleMessageType::CrawlResult => message
+                .crawl_target
+                .filter(|target| target.status_code.is_some())
+                .map(ScanEvent::Found),
+            ConsoleMessageType::Finish => Some(ScanEvent::Finished(message.data)),
+            _ => None,
+        }
+    }
+
+    fn into_result(self) -> Option<CrawlTarget> {
+        match self {
+            ScanEvent::Found(target) | ScanEvent::Hit(target) => Some(target),
+            _ => None,
+        }
+    }
+}
+
+/// A scan set up in code rather than from the command line. Nothing is printed; what's found
+/// comes back from [`Rinzler::events`], [`Rinzler::scan`] or a handler given to
+/// [`RinzlerBuilder::on_result`].
+pub struct Rinzler {
+    settings: RinzlerSettings,
+    on_result: Option<ResultHandler>,
+}
+
+impl Rinzler {
+    pub fn builder() -> RinzlerBuilder {
+        RinzlerBuilder {
+            settings: RinzlerSettings::default(),
+            recurse: None,
+            on_result: None,
+        }
+    }
+
+    /// Starts the scan and streams everything it reports. The stream ends after
+    /// [`ScanEvent::Finished`]. Must be called from within a tokio runtime.
+    pub fn events(&self) -> impl Stream<Item = ScanEvent> {
+        let (console_sender, console_receiver) = bounded(CONSOLE_BACKLOG);
+        let (events, stream) = mpsc::unbounded();
+        let settings = self.settings.clone();
+        let (throttle, skipped, progress) = RinzlerApplication::shared_state(&settings);
+        let failures = Arc::clone(&progress);
+        tokio::spawn(async move {
+            let outcome = RinzlerApplication::scan(
+                settings,
+                console_sender.clone(),
+                throttle,
+                skipped,
+                progress,
+            )
+            .await;
+            RinzlerApplication::inform_console_to_exit(outcome, console_sender);
+        });
+        let on_result = self.on_result.clone();
+        // the crawlers report to the console over a blocking channel, so it's read on its own thread
+        tokio::task::spawn_blocking(move || {
+            let mut errors_sent = 0;
+            loop {
+                let message = console_receiver.recv_timeout(ERROR_POLL_INTERVAL);
+                for failure in failures.failures_since(errors_sent) {
+                    errors_sent += 1;
+                    let _ = events.unbounded_send(ScanEvent::Error(failure));
+                }
+                let event = match message {
+                    Ok(message) => ScanEvent::from_message(message),
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                let finished = matches!(event, Some(ScanEvent::Finished(_)));
+                if let (Some(handler), Some(ScanEvent::Found(target) | ScanEvent::Hit(target))) =
+                    (&on_result, &event)
+                {
+                    handler(target);
+                }
+                if let Some(event) = event {
+                    let _ = events.unbounded_send(event);
+                }
+                if finished {
+                    break;
+                }
+            }
+        });
+        stream
+    }
+
+    /// Starts the scan and streams each page it finds, whether crawled or force browsed.
+    pub fn scan(&self) -> impl Stream<Item = CrawlTarget> {
+        self.events()
+            .filter_map(|event| future::ready(event.into_result()))
+    }
+
+    /// Runs the scan to the end, for when results are only wanted through
+    /// [`RinzlerBuilder::on_result`].
+    pub async fn run(&self) -> Result<String, String> {
+        let mut events = Box::pin(self.events());
+        while let Some(event) = events.next().await {
+            if let ScanEvent::Finished(outcome) = event {
+                return outcome;
+            }
+        }
+        Err("the scan stopped without finishing".to_string())
+    }
+}
+
+pub struct RinzlerBuilder {
+    settings: RinzlerSettings,
+    recurse: Option<bool>,
+    on_result: Option<ResultHandler>,
+}
+
+impl RinzlerBuilder {
+    /// Adds a URL to scan. Can be called multiple times.
+    pub fn host(mut self, url: impl Into<String>) -> RinzlerBuilder {
+        self.settings.hosts.push(url.into());
+        self
+    }
+
+    /// Force browses each host with these words. Like the command line, giving a wordlist turns
+    /// off crawling unless [`RinzlerBuilder::recurse`] turns it back on.
+    pub fn wordlist(mut self, words: Vec<String>) -> RinzlerBuilder {
+        self.settings.wordlist = Some(Arc::new(words));
+        self
+    }
+
+    pub fn recurse(mut self, recurse: bool) -> RinzlerBuilder {
+        self.recurse = Some(recurse);
+        self
+    }
+
+    pub fn order(mut self, order: CrawlOrder) -> RinzlerBuilder {
+        self.settings.order = order;
+        self
+    }
+
+    pub fn scoped(mut self, scoped: bool) -> RinzlerBuilder {
+        self.settings.scoped = scoped;
+        self
+    }
+
+    /// The most requests kept in flight at once.
+    pub fn threads(mut self, threads: usize) -> RinzlerBuilder {
+        self.settings.max_threads = threads.max(1);
+        self
+    }
+
+    /// Milliseconds to wait before each request.
+    pub fn rate_limit(mut self, millis: u64) -> RinzlerBuilder {
+        self.settings.rate_limit = millis;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> RinzlerBuilder {
+        self.settings.user_agent = user_agent.into();
+        self
+    }
+
+    /// Sends this header with every request. Can be called multiple times.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> RinzlerBuilder {
+        self.settings.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Calls `handler` with each page found, as well as it being streamed. It's called from a
+    /// blocking thread, so it's fine for it to do I/O.
+    pub fn on_result<F>(mut self, handler: F) -> RinzlerBuilder
+    where
+        F: Fn(&CrawlTarget) + Send + Sync + 'static,
+    {
+        self.on_result = Some(Arc::new(handler));
+        self
+    }
+
+    /// Changes any other setting, for the options that don't have a method of their own.
+    pub fn configure<F>(mut self, configure: F) -> RinzlerBuilder
+    where
+        F: FnOnce(&mut RinzlerSettings),
+    {
+        configure(&mut self.settings);
+        self
+    }
+
+    pub fn build(mut self) -> Rinzler {
+        self.settings.recurse = self.recurse.unwrap_or(self.settings.wordlist.is_none());
+        self.settings.finish();
+        Rinzler {
+            settings: self.settings,
+            on_result: self.on_result,
+        }
+    }
+}
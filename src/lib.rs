@@ -0,0 +1,28 @@
+//! rinzler's crawler and force browser, for embedding in other tools.
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use rnz::Rinzler;
+//!
+//! # async fn scan() {
+//! let rinzler = Rinzler::builder().host("https://crawler-test.com").build();
+//! let mut results = rinzler.scan();
+//! while let Some(result) = results.next().await {
+//!     println!("{:?} {}", result.status_code, result.url);
+//! }
+//! # }
+//! ```
+mod app;
+#[doc(hidden)]
+pub mod cli;
+mod client;
+mod config;
+mod crawler;
+mod engine;
+mod metrics;
+mod ui;
+
+pub use config::{CrawlOrder, RinzlerSettings};
+pub use crawler::crawl_target::CrawlTarget;
+pub use crawler::request_errors::{FailureKind, RequestError};
+pub use engine::{Rinzler, RinzlerBuilder, ScanEvent};
@@ -1,16 +1,4 @@
-use app::RinzlerApplication;
-use config::parse_cmd_line;
-
-mod app;
-mod client;
-mod config;
-mod crawler;
-mod ui;
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let settings = parse_cmd_line();
-    let app = RinzlerApplication::from_settings(settings);
-    app.run().await?;
-    Ok(())
+    rnz::cli::run().await
 }
@@ -0,0 +1,96 @@
+use crate::crawler::crawl_progress::CrawlProgress;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, error, info};
+
+// serves the scan's counters in the Prometheus text format on every request, whatever the path
+pub(crate) async fn serve(addr: SocketAddr, progress: Arc<CrawlProgress>) {
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(why) => {
+            error!("metrics endpoint couldn't listen on {}: {}", addr, why);
+            return;
+        }
+    };
+    info!("Serving metrics on http://{}/metrics", addr);
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let progress = Arc::clone(&progress);
+                tokio::spawn(async move {
+                    if let Err(why) = respond(stream, &progress).await {
+                        debug!("metrics request failed: {}", why);
+                    }
+                });
+            }
+            Err(why) => debug!("metrics connection failed: {}", why),
+        }
+    }
+}
+
+async fn respond(mut stream: TcpStream, progress: &CrawlProgress) -> std::io::Result<()> {
+    let mut request = [0; 1024];
+    let _ = stream.read(&mut request).await?;
+
+    let body = render(progress);
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn render(progress: &CrawlProgress) -> String {
+    let metrics = [
+        (
+            "rinzler_requests_total",
+            "counter",
+            "Requests sent, including retries and redirects.",
+            progress.requests() as f64,
+        ),
+        (
+            "rinzler_errors_total",
+            "counter",
+            "Requests that failed without a response.",
+            progress.errors() as f64,
+        ),
+        (
+            "rinzler_findings_total",
+            "counter",
+            "Results that passed the status filters.",
+            progress.findings() as f64,
+        ),
+        (
+            "rinzler_crawled_total",
+            "counter",
+            "Pages crawled.",
+            progress.crawled() as f64,
+        ),
+        (
+            "rinzler_queue_depth",
+            "gauge",
+            "Pages waiting to be crawled.",
+            progress.queued() as f64,
+        ),
+        (
+            "rinzler_requests_per_second",
+            "gauge",
+            "Average requests per second since the scan started.",
+            progress.requests_per_second(),
+        ),
+    ];
+
+    let mut body = String::new();
+    for (name, kind, help, value) in metrics {
+        let _ = writeln!(body, "# HELP {} {}", name, help);
+        let _ = writeln!(body, "# TYPE {} {}", name, kind);
+        let _ = writeln!(body, "{} {}", name, value);
+    }
+    body
+}
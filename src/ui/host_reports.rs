@@ -0,0 +1,99 @@
+use crate::config::ResultFormat;
+use crate::crawler::crawl_target::CrawlTarget;
+use crate::crawler::request_errors::RequestError;
+use crate::crawler::response_times::{self, Timings};
+use crate::ui::json_report;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use url::Url;
+
+/// Writes the results for each host to a file of its own in `dir`.
+pub fn write(
+    dir: &str,
+    format: ResultFormat,
+    results: &[CrawlTarget],
+    timings: &[Timings],
+    errors: &[RequestError],
+) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut by_host: BTreeMap<String, Vec<CrawlTarget>> = BTreeMap::new();
+    for result in results {
+        by_host
+            .entry(host_of(&result.url))
+            .or_default()
+            .push(result.clone());
+    }
+    for (host, results) in &by_host {
+        let path = Path::new(dir).join(format!("{}.{}", file_name(host), format));
+        match format {
+            ResultFormat::Json => {
+                let timings: Vec<Timings> = timings
+                    .iter()
+                    .filter(|timing| timing.host == *host)
+                    .cloned()
+                    .collect();
+                let errors: Vec<RequestError> = errors
+                    .iter()
+                    .filter(|error| host_of(&error.url) == *host)
+                    .cloned()
+                    .collect();
+                json_report::write(&path.to_string_lossy(), results, &timings, &errors)?
+            }
+            ResultFormat::Csv => fs::write(path, to_csv(results))?,
+        }
+    }
+    Ok(())
+}
+
+fn host_of(url: &str) -> String {
+    Url::parse(url)
+        .map(|url| response_times::host_of(&url))
+        .unwrap_or_default()
+}
+
+// a port's colon isn't allowed in file names everywhere
+fn file_name(host: &str) -> String {
+    match host {
+        "" => "unknown".to_string(),
+        host => host
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' => c,
+                _ => '_',
+            })
+            .collect(),
+    }
+}
+
+pub fn to_csv(results: &[CrawlTarget]) -> String {
+    let mut csv = String::from("url,status,method,response_time_ms,content_encoding\n");
+    for result in results {
+        let row = [
+            result.url.clone(),
+            result
+                .status_code
+                .map(|status| status.to_string())
+                .unwrap_or_default(),
+            result.method.clone(),
+            result
+                .response_time_ms
+                .map(|millis| millis.to_string())
+                .unwrap_or_default(),
+            result.content_encoding.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
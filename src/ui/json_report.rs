@@ -0,0 +1,33 @@
+use crate::crawler::crawl_target::CrawlTarget;
+use crate::crawler::request_errors::RequestError;
+use crate::crawler::response_times::Timings;
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+
+pub fn write(
+    path: &str,
+    results: &[CrawlTarget],
+    timings: &[Timings],
+    errors: &[RequestError],
+) -> io::Result<()> {
+    let report = json!({
+        "results": results.iter().map(|result| result.to_json()).collect::<Vec<Value>>(),
+        "timings": timings.iter().map(|timing| timing.to_json()).collect::<Vec<Value>>(),
+        "errors": errors.iter().map(|error| error.to_json()).collect::<Vec<Value>>(),
+    });
+    fs::write(path, serde_json::to_string_pretty(&report)?)
+}
+
+pub fn read(path: &str) -> io::Result<Vec<CrawlTarget>> {
+    let report: Value = fs::read_to_string(path)
+        .and_then(|contents| Ok(serde_json::from_str(&contents)?))
+        .map_err(|why| io::Error::new(why.kind(), format!("'{}': {}", path, why)))?;
+    let results = report["results"].as_array().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("'{}' has no results", path),
+        )
+    })?;
+    Ok(results.iter().filter_map(CrawlTarget::from_json).collect())
+}
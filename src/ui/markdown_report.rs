@@ -0,0 +1,60 @@
+use crate::config::RinzlerSettings;
+use crate::crawler::crawl_target::CrawlTarget;
+use chrono::Local;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+
+pub fn write(path: &str, settings: &RinzlerSettings, results: &[CrawlTarget]) -> io::Result<()> {
+    fs::write(path, render(settings, results))
+}
+
+fn render(settings: &RinzlerSettings, results: &[CrawlTarget]) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "# rinzler report\n");
+    let _ = writeln!(
+        report,
+        "Generated {} by rinzler v{}\n",
+        Local::now().format("%Y-%m-%d %T%z"),
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let _ = writeln!(report, "## Targets\n");
+    for host in &settings.hosts {
+        let _ = writeln!(report, "- {}", host);
+    }
+
+    let _ = writeln!(
+        report,
+        "\n## Settings\n\n```\n{}\n```\n",
+        settings.to_string().trim_end()
+    );
+
+    let _ = writeln!(report, "## Findings\n");
+    let mut by_status: BTreeMap<u16, Vec<&CrawlTarget>> = BTreeMap::new();
+    for result in results {
+        if let Some(status_code) = result.status_code {
+            by_status.entry(status_code).or_default().push(result);
+        }
+    }
+    if by_status.is_empty() {
+        let _ = writeln!(report, "Nothing was found.");
+    }
+    for (status_code, results) in by_status {
+        let _ = writeln!(report, "### {} ({})\n", status_code, results.len());
+        let _ = writeln!(report, "| Method | URL | Time |");
+        let _ = writeln!(report, "|--------|-----|------|");
+        for result in results {
+            let _ = writeln!(
+                report,
+                "| {} | {} | {} |",
+                result.method,
+                result.url.replace('|', "\\|"),
+                result.timestamp.format("%T%.3f%z")
+            );
+        }
+        let _ = writeln!(report);
+    }
+    report
+}
@@ -1 +1,8 @@
+pub(crate) mod host_reports;
+pub(crate) mod json_report;
+pub(crate) mod markdown_report;
+pub(crate) mod result_sink;
 pub(crate) mod rinzler_console;
+pub(crate) mod sarif_report;
+pub(crate) mod scan_diff;
+pub(crate) mod webhook;
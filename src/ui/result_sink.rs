@@ -0,0 +1,125 @@
+use crate::config::{ResultFormat, RinzlerSettings};
+use crate::crawler::crawl_target::CrawlTarget;
+use crate::crawler::request_errors::RequestError;
+use crate::crawler::response_times::Timings;
+use crate::ui::{host_reports, json_report, markdown_report};
+use std::fs;
+use std::io;
+
+/// Somewhere the scan's results are written. Each output asked for on the command line is a sink
+/// of its own, so one scan can write any number of them.
+pub trait ResultSink {
+    fn on_result(&mut self, result: &CrawlTarget);
+    fn on_finish(&mut self, timings: &[Timings], errors: &[RequestError]) -> io::Result<()>;
+    /// The file or directory written to, for saying where a write failed.
+    fn destination(&self) -> &str;
+}
+
+pub fn from_settings(settings: &RinzlerSettings) -> Vec<Box<dyn ResultSink>> {
+    let mut sinks: Vec<Box<dyn ResultSink>> = vec![];
+    if let Some(path) = &settings.output_md {
+        sinks.push(Box::new(MarkdownSink {
+            path: path.clone(),
+            settings: settings.clone(),
+            results: vec![],
+        }));
+    }
+    if let Some(path) = &settings.output_json {
+        sinks.push(Box::new(JsonSink {
+            path: path.clone(),
+            results: vec![],
+        }));
+    }
+    if let Some(path) = &settings.output_csv {
+        sinks.push(Box::new(CsvSink {
+            path: path.clone(),
+            results: vec![],
+        }));
+    }
+    if let Some(dir) = &settings.output_dir {
+        sinks.push(Box::new(HostReportsSink {
+            dir: dir.clone(),
+            format: settings.output_dir_format,
+            results: vec![],
+        }));
+    }
+    sinks
+}
+
+struct MarkdownSink {
+    path: String,
+    settings: RinzlerSettings,
+    results: Vec<CrawlTarget>,
+}
+
+impl ResultSink for MarkdownSink {
+    fn on_result(&mut self, result: &CrawlTarget) {
+        self.results.push(result.clone());
+    }
+
+    fn on_finish(&mut self, _: &[Timings], _: &[RequestError]) -> io::Result<()> {
+        markdown_report::write(&self.path, &self.settings, &self.results)
+    }
+
+    fn destination(&self) -> &str {
+        &self.path
+    }
+}
+
+struct JsonSink {
+    path: String,
+    results: Vec<CrawlTarget>,
+}
+
+impl ResultSink for JsonSink {
+    fn on_result(&mut self, result: &CrawlTarget) {
+        self.results.push(result.clone());
+    }
+
+    fn on_finish(&mut self, timings: &[Timings], errors: &[RequestError]) -> io::Result<()> {
+        json_report::write(&self.path, &self.results, timings, errors)
+    }
+
+    fn destination(&self) -> &str {
+        &self.path
+    }
+}
+
+struct CsvSink {
+    path: String,
+    results: Vec<CrawlTarget>,
+}
+
+impl ResultSink for CsvSink {
+    fn on_result(&mut self, result: &CrawlTarget) {
+        self.results.push(result.clone());
+    }
+
+    fn on_finish(&mut self, _: &[Timings], _: &[RequestError]) -> io::Result<()> {
+        fs::write(&self.path, host_reports::to_csv(&self.results))
+    }
+
+    fn destination(&self) -> &str {
+        &self.path
+    }
+}
+
+struct HostReportsSink {
+    dir: String,
+    format: ResultFormat,
+    results: Vec<CrawlTarget>,
+}
+
+impl ResultSink for HostReportsSink {
+    fn on_result(&mut self, result: &CrawlTarget) {
+        self.results.push(result.clone());
+    }
+
+    fn on_finish(&mut self, timings: &[Timings], errors: &[RequestError]) -> io::Result<()> {
+        host_reports::write(&self.dir, self.format, &self.results, timings, errors)
+    }
+
+    fn destination(&self) -> &str {
+        &self.dir
+    }
+}
@@ -1,26 +1,56 @@
-use crate::config::RinzlerSettings;
+use crate::client::throttle::Throttle;
+use crate::config::{ProgressDisplay, RinzlerSettings};
+use crate::crawler::crawl_progress::CrawlProgress;
 use crate::crawler::crawl_target::CrawlTarget;
+use crate::crawler::request_errors::FailureKind;
+use crate::crawler::skipped_targets::SkippedTargets;
+use crate::ui::result_sink::{self, ResultSink};
 use colored::Colorize;
 use console::{Emoji, Term};
-use crossbeam::channel::{unbounded, Receiver};
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use std::collections::HashMap;
+use crossbeam::channel::{never, unbounded, Receiver, RecvTimeoutError, Sender, TrySendError};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use std::{io, thread};
+use tracing::{error, Level};
 
 static SPIDER_WEB: Emoji = Emoji("🕸️", "|");
 static HEARTS: Emoji = Emoji("💖💖💖", "<3 ");
 static GREEN_CHECK: Emoji = Emoji("  ✅  ", ":");
 static CROSS_MARK: Emoji = Emoji("  ❌  ", ":");
 
+// how long to wait for a message before checking whether 'enter' was pressed
+const STDIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+// long enough to smooth over bursts, short enough to show a slowdown as it happens
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
 pub enum ConsoleMessageType {
     ForceBrowseStart,
     ForceBrowseProgress,
     ForceBrowseHit,
+    ForceBrowseProtected,
     ForceBrowseAttempt,
+    DownloadStart,
+    DownloadProgress,
+    DownloadFinish,
     Finish,
     Abort,
     CrawlResult,
-    NONE,
+    CrawlProgress,
+    InScopeLink,
+    OutOfScopeLink,
+    SecurityFinding,
+    TlsInfo,
+    Preflight,
+    FormFound,
+    HarvestedEmail,
+    HarvestedComment,
+    SourceMapSource,
+    ApiEndpoint,
+    ExternalDomain,
+    None,
 }
 
 pub struct ConsoleMessage {
@@ -33,11 +63,61 @@ pub struct ConsoleMessage {
 impl Clone for ConsoleMessage {
     fn clone(&self) -> Self {
         ConsoleMessage {
-            message_type: ConsoleMessageType::NONE,
+            message_type: ConsoleMessageType::None,
             data: self.data.clone(),
             original_target: self.original_target.clone(),
             crawl_target: self.crawl_target.clone(),
-            total: self.total.clone(),
+            total: self.total,
+        }
+    }
+}
+
+/// The crawlers' end of the console's channel. Sending never blocks the async runtime's threads:
+/// when the console has fallen behind, the wait for room happens on a blocking thread instead.
+#[derive(Clone)]
+pub struct ConsoleSender {
+    sender: Sender<ConsoleMessage>,
+}
+
+impl ConsoleSender {
+    pub fn new(sender: Sender<ConsoleMessage>) -> ConsoleSender {
+        ConsoleSender { sender }
+    }
+
+    pub async fn send(&self, message: ConsoleMessage) {
+        if let Err(TrySendError::Full(message)) = self.sender.try_send(message) {
+            let sender = self.sender.clone();
+            let _ = tokio::task::spawn_blocking(move || sender.send(message).is_ok()).await;
+        }
+    }
+}
+
+struct RequestRate {
+    samples: VecDeque<(Instant, usize)>,
+}
+
+impl RequestRate {
+    fn new() -> RequestRate {
+        RequestRate {
+            samples: VecDeque::new(),
+        }
+    }
+
+    fn sample(&mut self, requests: usize) -> f64 {
+        let now = Instant::now();
+        self.samples.push_back((now, requests));
+        while let Some((at, _)) = self.samples.front() {
+            if now.duration_since(*at) <= RATE_WINDOW {
+                break;
+            }
+            self.samples.pop_front();
+        }
+        let (since, from) = self.samples.front().copied().unwrap_or((now, requests));
+        let elapsed = now.duration_since(since).as_secs_f64();
+        if elapsed > 0.0 {
+            (requests - from) as f64 / elapsed
+        } else {
+            0.0
         }
     }
 }
@@ -45,6 +125,9 @@ impl Clone for ConsoleMessage {
 pub(crate) struct RinzlerConsole {
     settings: RinzlerSettings,
     message_receiver: Receiver<ConsoleMessage>,
+    throttle: Arc<Throttle>,
+    skipped: Arc<SkippedTargets>,
+    progress: Arc<CrawlProgress>,
     terminal: Term,
 }
 
@@ -52,120 +135,646 @@ impl RinzlerConsole {
     pub fn new(
         settings: RinzlerSettings,
         message_receiver: Receiver<ConsoleMessage>,
+        throttle: Arc<Throttle>,
+        skipped: Arc<SkippedTargets>,
+        progress: Arc<CrawlProgress>,
     ) -> Result<RinzlerConsole, io::Error> {
         Ok(RinzlerConsole {
             settings,
             message_receiver,
+            throttle,
+            skipped,
+            progress,
             terminal: Term::stdout(),
         })
     }
 
     pub fn clear(self) -> RinzlerConsole {
-        let _ = self.terminal.clear_screen();
+        let wanted = self.settings.clear_screen && !self.settings.quiet && !self.settings.urls_only;
+        if wanted && self.terminal.is_term() {
+            let _ = self.terminal.clear_screen();
+        }
         self
     }
 
+    // with --urls-only, or when stdout is piped, keep everything except results off stdout
+    fn status_terminal(&self) -> Term {
+        if self.settings.urls_only || !self.terminal.is_term() {
+            Term::stderr()
+        } else {
+            self.terminal.clone()
+        }
+    }
+
     fn spawn_stdin_channel() -> Receiver<String> {
         let (tx, rx) = unbounded();
         thread::spawn(move || loop {
             let mut buffer = String::new();
-            io::stdin().read_line(&mut buffer).unwrap();
-            tx.send(buffer).unwrap();
+            // stop listening once stdin is closed or the console has gone away
+            match io::stdin().read_line(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    if tx.send(buffer).is_err() {
+                        break;
+                    }
+                }
+            }
         });
         rx
     }
 
     pub fn render(self) {
-        let m = MultiProgress::new();
+        let m = if self.settings.urls_only {
+            MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+        } else {
+            let refresh_hz = (1000 / self.settings.progress_refresh_ms).max(1);
+            MultiProgress::with_draw_target(ProgressDrawTarget::stderr_with_hz(refresh_hz))
+        };
+        let status_terminal = self.status_terminal();
+        let mut sinks = result_sink::from_settings(&self.settings);
+        // shared forms and footers appear on every page, so each is only reported once
+        let mut forms: HashSet<String> = HashSet::new();
+        let mut harvested: HashSet<String> = HashSet::new();
+        // sorted, since they're only listed once the scan has finished
+        let mut external_domains: BTreeSet<String> = BTreeSet::new();
+        let mut depths: HashMap<String, usize> = HashMap::new();
         let mut ongoing_scans: HashMap<CrawlTarget, ProgressBar> = HashMap::new();
-        let stdin_channel = RinzlerConsole::spawn_stdin_channel();
+        let mut downloads: HashMap<CrawlTarget, ProgressBar> = HashMap::new();
+        let mut scan_order: Vec<CrawlTarget> = vec![];
+        let mut errors_shown = 0;
+        let mut crawl_progress: Option<ProgressBar> = None;
+        let request_status = m.add(Self::get_request_status_line());
+        let mut request_rate = RequestRate::new();
+        let (mut words_tried, mut words_total) = (0, 0);
+        // stdin is already being consumed as the wordlist or targets, so there's nothing to listen to for 'enter'
+        let stdin_channel = if self.settings.reads_stdin() {
+            never()
+        } else {
+            RinzlerConsole::spawn_stdin_channel()
+        };
         loop {
             if let Ok(key) = stdin_channel.try_recv() {
-                if key == "\n" {
-                    break;
+                match key.trim() {
+                    "" => break,
+                    "p" => {
+                        self.throttle.pause();
+                        let _ = status_terminal.write_line(&format!(
+                            "{} no new requests will be sent until 'r' is entered",
+                            "paused".yellow()
+                        ));
+                    }
+                    "r" => {
+                        self.throttle.resume();
+                        let _ = status_terminal.write_line(&format!("{}", "resumed".green()));
+                    }
+                    "s" => {
+                        // the longest running force browse is the one most worth giving up on
+                        let current = scan_order.iter().find_map(|ct| {
+                            let pb = ongoing_scans.get(ct)?;
+                            let running = pb.position() < pb.length();
+                            (!self.skipped.contains(ct) && running).then_some((ct, pb))
+                        });
+                        if let Some((ct, pb)) = current {
+                            self.skipped.skip(ct);
+                            pb.abandon();
+                            let _ = status_terminal.write_line(&format!(
+                                "{} {}",
+                                "skipped".yellow(),
+                                ct.url.as_str().dimmed()
+                            ));
+                        }
+                    }
+                    _ => {}
                 }
             }
-            let console_message = self.message_receiver.try_recv();
+            if self.settings.show_errors && !self.settings.urls_only {
+                for failure in self.progress.failures_since(errors_shown) {
+                    errors_shown += 1;
+                    let _ = status_terminal.write_line(&format!(
+                        "{} {} {} {}",
+                        "fail".red(),
+                        failure.kind,
+                        failure.url.as_str().cyan(),
+                        failure.message.as_str().dimmed()
+                    ));
+                }
+            }
+            let requests = self.progress.requests();
+            let mut status = format!(
+                "{} requests sent, {:.1}/s",
+                requests,
+                request_rate.sample(requests)
+            );
+            if self.minimal() {
+                status.push_str(&format!(", {}", self.progress.summary()));
+                if words_total > 0 {
+                    status.push_str(&format!(", {}/{} words tried", words_tried, words_total));
+                }
+                status.push_str(&format!(", {} found", self.progress.findings()));
+            }
+            request_status.set_message(status);
+            let console_message = self.message_receiver.recv_timeout(STDIN_POLL_INTERVAL);
+            if let Err(RecvTimeoutError::Disconnected) = console_message {
+                break;
+            }
             if let Ok(command) = console_message {
                 match command.message_type {
-                    ConsoleMessageType::NONE => {}
+                    ConsoleMessageType::None => {}
                     ConsoleMessageType::ForceBrowseStart => {
-                        let pb = m.add(ProgressBar::new(command.total.unwrap()));
-                        pb.set_style(ProgressStyle::default_bar()
-                            .template("{spinner:.green} {msg:50}\n[{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos:>7}/{len:7} ({eta}) ")
-                            .with_key("eta", |state| format!("{:.1}s", state.eta().as_secs_f64()))
-                            .progress_chars("#>-"));
-                        ongoing_scans.insert(command.crawl_target.unwrap(), pb);
+                        let ct = command.crawl_target.unwrap();
+                        words_total += command.total.unwrap();
+                        let pb = self.add_progress(&m, ProgressBar::new(command.total.unwrap()));
+                        pb.set_style(self.get_force_browse_style());
+                        pb.set_prefix(ct.url.clone());
+                        if !scan_order.contains(&ct) {
+                            scan_order.push(ct.clone());
+                        }
+                        ongoing_scans.insert(ct, pb);
                     }
+                    // a late message can arrive once the target's crawl result has removed its bar
                     ConsoleMessageType::ForceBrowseProgress => {
-                        let pb = &ongoing_scans.get(&command.crawl_target.unwrap()).unwrap();
-                        pb.inc(1);
+                        words_tried += 1;
+                        if let Some(pb) = ongoing_scans.get(&command.crawl_target.unwrap()) {
+                            pb.inc(1);
+                        }
                     }
                     ConsoleMessageType::ForceBrowseHit => {
-                        let ct = &command.crawl_target.clone();
-                        let pb = &ongoing_scans.get(&ct.clone().unwrap()).unwrap();
-                        pb.println(format!("{}", &ct.clone().unwrap()));
-                        pb.inc(1);
+                        let ct = command.crawl_target.unwrap();
+                        Self::record(&mut sinks, &ct);
+                        let pb = ongoing_scans.get(&ct);
+                        if self.settings.urls_only {
+                            self.print_result("hit", &ct, None);
+                        } else {
+                            let above = pb
+                                .map_or(&request_status, |pb| self.line_above(pb, &request_status));
+                            above.println(format!("{}", &ct));
+                            if let Some(redirects) = self.describe_redirects(&ct) {
+                                above.println(redirects);
+                            }
+                        }
+                        words_tried += 1;
+                        if let Some(pb) = pb {
+                            pb.inc(1);
+                        }
+                    }
+                    ConsoleMessageType::ForceBrowseProtected => {
+                        let ct = command.crawl_target.unwrap();
+                        Self::record(&mut sinks, &ct);
+                        let pb = ongoing_scans.get(&ct);
+                        if self.settings.urls_only {
+                            self.print_result("protected", &ct, None);
+                        } else {
+                            let above = pb
+                                .map_or(&request_status, |pb| self.line_above(pb, &request_status));
+                            above.println(format!("{} {}", "locked".magenta().bold(), ct));
+                        }
+                        words_tried += 1;
+                        if let Some(pb) = pb {
+                            pb.inc(1);
+                        }
                     }
                     ConsoleMessageType::ForceBrowseAttempt => {
-                        let c3 = command.clone();
-                        let old = c3.original_target.unwrap();
-                        let pb = &ongoing_scans.get(&old).unwrap();
-                        let new = c3.crawl_target.unwrap();
-                        pb.set_message(format!("{}", new.url));
+                        let old = command.original_target.unwrap();
+                        if let Some(pb) = ongoing_scans.get(&old) {
+                            pb.set_message(command.crawl_target.unwrap().url);
+                        }
+                    }
+                    // a download of unknown length spins, showing how much has arrived so far
+                    ConsoleMessageType::DownloadStart => {
+                        let ct = command.crawl_target.unwrap();
+                        let pb = match command.total {
+                            Some(length) => self.add_progress(&m, ProgressBar::new(length)),
+                            None => self.add_progress(&m, ProgressBar::new_spinner()),
+                        };
+                        pb.set_style(self.get_download_style(command.total.is_some()));
+                        pb.set_prefix(ct.url.clone());
+                        downloads.insert(ct, pb);
+                    }
+                    ConsoleMessageType::DownloadProgress => {
+                        let ct = command.crawl_target.unwrap();
+                        if let Some(pb) = downloads.get(&ct) {
+                            pb.set_position(command.total.unwrap_or_default());
+                        }
+                    }
+                    ConsoleMessageType::DownloadFinish => {
+                        let ct = command.crawl_target.unwrap();
+                        if let Some(pb) = downloads.remove(&ct) {
+                            pb.finish_and_clear();
+                        }
+                    }
+                    // minimal mode has these counts on its status line already
+                    ConsoleMessageType::CrawlProgress if !self.minimal() => {
+                        let pb = crawl_progress
+                            .get_or_insert_with(|| m.add(Self::get_crawl_progress_spinner()));
+                        pb.set_message(command.data.unwrap_or_default());
+                        pb.tick();
+                    }
+                    ConsoleMessageType::CrawlProgress => {}
+                    ConsoleMessageType::InScopeLink => {
+                        let link = command.crawl_target.unwrap();
+                        if self.settings.urls_only {
+                            self.print_result("link", &link, None);
+                        } else {
+                            println!("{} {}", "in ".green(), link.url.as_str().cyan());
+                        }
+                    }
+                    ConsoleMessageType::OutOfScopeLink => {
+                        let link = command.crawl_target.unwrap();
+                        if self.settings.urls_only {
+                            self.print_result("external_link", &link, None);
+                        } else {
+                            println!("{} {}", "out".yellow(), link.url.as_str().dimmed());
+                        }
+                    }
+                    ConsoleMessageType::SecurityFinding => {
+                        let target = command.crawl_target.unwrap();
+                        if self.settings.urls_only {
+                            let finding = command.data.unwrap_or_default();
+                            self.print_result("finding", &target, Some(&finding));
+                        } else {
+                            println!(
+                                "{} {} {}",
+                                "vuln".red().bold(),
+                                command.data.unwrap_or_default(),
+                                target.url.as_str().cyan()
+                            );
+                        }
+                    }
+                    ConsoleMessageType::TlsInfo => {
+                        if !self.settings.urls_only {
+                            let target = command.crawl_target.unwrap();
+                            // an Err means the certificate has expired or is about to
+                            let details = match command.data {
+                                Ok(details) => details.normal(),
+                                Err(details) => details.yellow(),
+                            };
+                            println!(
+                                "{} {} {}",
+                                "tls ".blue(),
+                                target.url.as_str().cyan(),
+                                details
+                            );
+                        }
+                    }
+                    ConsoleMessageType::Preflight => {
+                        let target = command.crawl_target.unwrap();
+                        // an Err means the target couldn't be reached and won't be crawled
+                        let line = match command.data {
+                            Ok(status) => format!(
+                                "{} {} {}",
+                                "up  ".green(),
+                                target.url.as_str().cyan(),
+                                status
+                            ),
+                            Err(reason) => format!(
+                                "{} {} {}",
+                                "down".red(),
+                                target.url.as_str().cyan(),
+                                reason.red()
+                            ),
+                        };
+                        let _ = status_terminal.write_line(&line);
+                    }
+                    ConsoleMessageType::FormFound => {
+                        let form = command.data.unwrap_or_default();
+                        let shown = !self.settings.urls_only || self.settings.json_lines;
+                        if shown && forms.insert(form.clone()) {
+                            let target = command.crawl_target.unwrap();
+                            if self.settings.json_lines {
+                                self.print_result("form", &target, Some(&form));
+                            } else {
+                                println!(
+                                    "{} {} {}",
+                                    "form".magenta(),
+                                    form,
+                                    format!("(on {})", target.url).dimmed()
+                                );
+                            }
+                        }
+                    }
+                    ConsoleMessageType::HarvestedEmail | ConsoleMessageType::HarvestedComment => {
+                        let item = command.data.unwrap_or_default();
+                        let shown = !self.settings.urls_only || self.settings.json_lines;
+                        if shown && harvested.insert(item.clone()) {
+                            let label = match command.message_type {
+                                ConsoleMessageType::HarvestedEmail => "mail",
+                                _ => "note",
+                            };
+                            let target = command.crawl_target.unwrap();
+                            if self.settings.json_lines {
+                                self.print_result(label, &target, Some(&item));
+                            } else {
+                                println!(
+                                    "{} {} {}",
+                                    label.magenta(),
+                                    item,
+                                    format!("(on {})", target.url).dimmed()
+                                );
+                            }
+                        }
+                    }
+                    ConsoleMessageType::SourceMapSource => {
+                        let source = command.data.unwrap_or_default();
+                        let shown = !self.settings.urls_only || self.settings.json_lines;
+                        if shown && harvested.insert(source.clone()) {
+                            let target = command.crawl_target.unwrap();
+                            if self.settings.json_lines {
+                                self.print_result("source", &target, Some(&source));
+                            } else {
+                                println!(
+                                    "{} {} {}",
+                                    "src".magenta(),
+                                    source,
+                                    format!("(in {})", target.url).dimmed()
+                                );
+                            }
+                        }
+                    }
+                    ConsoleMessageType::ExternalDomain => {
+                        if let Ok(domain) = command.data {
+                            external_domains.insert(domain);
+                        }
+                    }
+                    ConsoleMessageType::ApiEndpoint => {
+                        if self.settings.urls_only {
+                            let details = command.data.unwrap_or_else(|details| details);
+                            self.print_result(
+                                "api",
+                                &command.crawl_target.unwrap(),
+                                Some(&details),
+                            );
+                        } else {
+                            // an Err means the status isn't one the spec documents
+                            let details = match command.data {
+                                Ok(details) => details.normal(),
+                                Err(details) => details.yellow(),
+                            };
+                            println!("{} {}", "api ".blue(), details);
+                        }
                     }
                     ConsoleMessageType::Finish => {
-                        let output = format!(
-                            "\n{} Scan Finished: {}\n",
-                            GREEN_CHECK,
-                            &command.data.unwrap().as_str().green()
-                        );
+                        if let Some(pb) = &crawl_progress {
+                            pb.finish();
+                        }
+                        request_status.finish_with_message(format!(
+                            "{} requests sent, {:.1}/s overall",
+                            self.progress.requests(),
+                            self.progress.requests_per_second()
+                        ));
+                        if !external_domains.is_empty() {
+                            let _ = status_terminal.write_line(&format!(
+                                "\n{} out of scope domains were linked to:",
+                                external_domains.len()
+                            ));
+                            for domain in &external_domains {
+                                let _ = status_terminal.write_line(&format!(
+                                    "{} {}",
+                                    "ext".yellow(),
+                                    domain.as_str().dimmed()
+                                ));
+                            }
+                        }
+                        let timings = self.progress.timings();
+                        if !self.settings.urls_only && !self.settings.quiet && !timings.is_empty() {
+                            let _ = status_terminal.write_line(&format!(
+                                "\n{:<30} {:>8} {:>6} {:>6} {:>6} {:>6}",
+                                "response times (ms)", "requests", "min", "median", "p95", "max"
+                            ));
+                            for timing in &timings {
+                                let _ = status_terminal.write_line(&timing.to_string());
+                            }
+                        }
+                        let failures = self.progress.failures_since(0);
+                        if !self.settings.urls_only && !failures.is_empty() {
+                            let mut by_kind: BTreeMap<FailureKind, usize> = BTreeMap::new();
+                            for failure in &failures {
+                                *by_kind.entry(failure.kind).or_default() += 1;
+                            }
+                            let _ = status_terminal
+                                .write_line(&format!("\n{} requests failed:", failures.len()));
+                            for (kind, count) in by_kind {
+                                let _ = status_terminal.write_line(&format!(
+                                    "{} {:<20} {:>8}",
+                                    "fail".red(),
+                                    kind.to_string(),
+                                    count
+                                ));
+                            }
+                        }
+                        for sink in sinks.iter_mut() {
+                            if let Err(why) = sink.on_finish(&timings, &failures) {
+                                error!(
+                                    "results couldn't be written to '{}': {}",
+                                    sink.destination(),
+                                    why
+                                );
+                            }
+                        }
+                        let output = match &command.data {
+                            Ok(reason) => {
+                                format!("\n{} Scan Finished: {}\n", GREEN_CHECK, reason.green())
+                            }
+                            Err(reason) => {
+                                format!("\n{} Scan Failed: {}\n", CROSS_MARK, reason.red())
+                            }
+                        };
 
-                        let _ = self.terminal.write_line(output.as_str());
+                        let _ = status_terminal.write_line(output.as_str());
                         break;
                     }
                     ConsoleMessageType::Abort => {
                         if let Err(error) = command.data {
                             let output = format!("\n{} Scan Failed: {}\n", CROSS_MARK, error.red());
 
-                            let _ = self.terminal.write_line(output.as_str());
+                            let _ = status_terminal.write_line(output.as_str());
                         };
                         break;
                     }
+                    ConsoleMessageType::CrawlResult if self.settings.urls_only => {
+                        if let Some(crawl_tgt) = command.crawl_target {
+                            if crawl_tgt.status_code.is_some() {
+                                Self::record(&mut sinks, &crawl_tgt);
+                                self.print_result("crawl", &crawl_tgt, None);
+                            }
+                        }
+                    }
                     ConsoleMessageType::CrawlResult => {
-                        let _ = if !self.settings.quiet {
+                        if let Some(crawl_tgt) = &command.crawl_target {
+                            if crawl_tgt.status_code.is_some() {
+                                Self::record(&mut sinks, crawl_tgt);
+                            }
+                        }
+                        if !self.settings.quiet {
                             if let Some(crawl_tgt) = command.crawl_target {
                                 if HashMap::contains_key(&ongoing_scans, &crawl_tgt) {
                                     let pb = ongoing_scans.remove(&crawl_tgt).unwrap();
+                                    scan_order.retain(|ct| ct != &crawl_tgt);
                                     if crawl_tgt.status_code.is_some() {
-                                        pb.finish_with_message(format!("{}", crawl_tgt));
+                                        let prefix = self.tree_prefix(
+                                            &mut depths,
+                                            &crawl_tgt,
+                                            &command.original_target,
+                                        );
+                                        let line = format!("{}{}", prefix, crawl_tgt);
+                                        if self.minimal() {
+                                            pb.finish_and_clear();
+                                            request_status.println(line);
+                                        } else {
+                                            pb.finish_with_message(line);
+                                        }
+                                        if let Some(redirects) = self.describe_redirects(&crawl_tgt)
+                                        {
+                                            self.line_above(&pb, &request_status)
+                                                .println(redirects);
+                                        }
                                     }
+                                } else if crawl_tgt.status_code.is_none() {
+                                    let pb = self.get_spinner(&m, &crawl_tgt);
+                                    ongoing_scans.insert(crawl_tgt, pb);
                                 } else {
-                                    if crawl_tgt.status_code.is_none() {
-                                        let pb = m.add(Self::get_spinner(&crawl_tgt));
-                                        ongoing_scans.insert(crawl_tgt, pb);
-                                    } else {
-                                        println!("{}", crawl_tgt);
+                                    let prefix = self.tree_prefix(
+                                        &mut depths,
+                                        &crawl_tgt,
+                                        &command.original_target,
+                                    );
+                                    println!("{}{}", prefix, crawl_tgt);
+                                    if let Some(redirects) = self.describe_redirects(&crawl_tgt) {
+                                        println!("{}", redirects);
                                     }
                                 }
                             }
                             for n in &ongoing_scans {
                                 n.1.inc(1);
                             }
-                        };
+                        }
                     }
                 }
             }
         }
     }
 
-    fn get_spinner(crawl_tgt: &CrawlTarget) -> ProgressBar {
+    // --json prints each result as a line of JSON, --urls-only just its url
+    fn print_result(&self, kind: &str, target: &CrawlTarget, detail: Option<&str>) {
+        if !self.settings.json_lines {
+            println!("{}", target.url);
+            return;
+        }
+        let mut line = target.to_json();
+        line["type"] = json!(kind);
+        if let Some(detail) = detail {
+            line["detail"] = json!(detail);
+        }
+        println!("{}", line);
+    }
+
+    // in tree mode each page is indented one level deeper than the page that linked to it
+    fn tree_prefix(
+        &self,
+        depths: &mut HashMap<String, usize>,
+        crawl_tgt: &CrawlTarget,
+        parent: &Option<CrawlTarget>,
+    ) -> String {
+        if !self.settings.tree {
+            return String::default();
+        }
+        let depth = parent
+            .as_ref()
+            .and_then(|parent| depths.get(&parent.url))
+            .map_or(0, |depth| depth + 1);
+        depths.entry(crawl_tgt.url.clone()).or_insert(depth);
+        match depth {
+            0 => String::default(),
+            _ => format!("{}└─ ", "   ".repeat(depth - 1)),
+        }
+    }
+
+    // the hops a result was redirected through are only shown in verbose mode
+    fn describe_redirects(&self, crawl_tgt: &CrawlTarget) -> Option<String> {
+        if self.settings.verbosity < Level::INFO || crawl_tgt.redirect_chain.is_empty() {
+            return None;
+        }
+        let hops: Vec<String> = crawl_tgt
+            .redirect_chain
+            .iter()
+            .map(|(status_code, url)| format!("    {} {} {}", "↳".dimmed(), status_code, url))
+            .collect();
+        Some(hops.join("\n"))
+    }
+
+    fn get_spinner(&self, m: &MultiProgress, crawl_tgt: &CrawlTarget) -> ProgressBar {
         let pb = ProgressBar::new_spinner().with_message(format!("{}", crawl_tgt));
         pb.set_style(
             ProgressStyle::default_spinner()
                 .tick_chars(RinzlerConsole::get_spinner_chars())
                 .template("{prefix:.bold.dim} {spinner} {wide_msg}"),
         );
+        self.add_progress(m, pb)
+    }
+
+    fn minimal(&self) -> bool {
+        self.settings.progress_display == ProgressDisplay::Minimal
+    }
+
+    // in minimal mode each target's progress is still kept, but only the status line is drawn
+    fn add_progress(&self, m: &MultiProgress, pb: ProgressBar) -> ProgressBar {
+        if self.minimal() {
+            pb.set_draw_target(ProgressDrawTarget::hidden());
+            pb
+        } else {
+            m.add(pb)
+        }
+    }
+
+    // a hidden bar drops what's printed through it, so those lines go above the status line
+    fn line_above<'a>(&self, pb: &'a ProgressBar, status: &'a ProgressBar) -> &'a ProgressBar {
+        if self.minimal() {
+            status
+        } else {
+            pb
+        }
+    }
+
+    fn get_force_browse_style(&self) -> ProgressStyle {
+        match self.settings.progress_display {
+            ProgressDisplay::Bar => ProgressStyle::default_bar()
+                .template("{spinner:.green} {msg:50}\n[{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos:>7}/{len:7} ({eta}) ")
+                .with_key("eta", |state| format!("{:.1}s", state.eta().as_secs_f64()))
+                .progress_chars("#>-"),
+            // minimal mode doesn't draw these bars, counting their words on its status line
+            ProgressDisplay::Spinner | ProgressDisplay::Minimal => ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] {pos}/{len} {wide_msg}"),
+        }
+    }
+
+    fn record(sinks: &mut [Box<dyn ResultSink>], result: &CrawlTarget) {
+        for sink in sinks.iter_mut() {
+            sink.on_result(result);
+        }
+    }
+
+    fn get_download_style(&self, known_length: bool) -> ProgressStyle {
+        match (self.settings.progress_display, known_length) {
+            (ProgressDisplay::Bar, true) => ProgressStyle::default_bar()
+                .template("{spinner:.green} {prefix}\n[{elapsed_precise}] [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}) ")
+                .progress_chars("#>-"),
+            (_, true) => ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] {bytes}/{total_bytes} {prefix}"),
+            (_, false) => ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] {bytes} {prefix}"),
+        }
+    }
+
+    fn get_crawl_progress_spinner() -> ProgressBar {
+        let pb = ProgressBar::new_spinner();
+        pb.set_style(
+            ProgressStyle::default_spinner().template("{spinner:.green} [{elapsed_precise}] {msg}"),
+        );
+        pb
+    }
+
+    fn get_request_status_line() -> ProgressBar {
+        let pb = ProgressBar::new_spinner();
+        pb.set_style(ProgressStyle::default_spinner().template("{msg:.dim}"));
         pb
     }
 
@@ -174,6 +783,9 @@ impl RinzlerConsole {
     }
 
     pub fn banner(self, settings_desc: String) -> RinzlerConsole {
+        if self.settings.urls_only || self.settings.quiet {
+            return self;
+        }
         let mut builder = string_builder::Builder::default();
 
         builder.append("           _             __\n");
@@ -187,10 +799,27 @@ impl RinzlerConsole {
         builder.append(format!("  {}\n", SPIDER_WEB));
         builder.append(format!("  {}    usage: rnz <URL>\n", SPIDER_WEB));
         builder.append(format!("  {}\n", SPIDER_WEB));
-        builder.append(format!("  {}    Press 'enter' to quit\n\n", SPIDER_WEB));
+        if self.settings.stdin_urls {
+            builder.append(format!(
+                "  {}    Reading target URLs from stdin\n\n",
+                SPIDER_WEB
+            ));
+        } else if self.settings.reads_wordlist_from_stdin() {
+            builder.append(format!(
+                "  {}    Reading wordlist from stdin\n\n",
+                SPIDER_WEB
+            ));
+        } else {
+            builder.append(format!(
+                "  {}    Press 'enter' to quit, or enter 'p' to pause, 'r' to resume and 's' to skip the current target\n\n",
+                SPIDER_WEB
+            ));
+        }
         builder.append(format!("{}\n", settings_desc));
 
-        print!("{}", builder.string().unwrap());
+        let _ = self
+            .status_terminal()
+            .write_str(builder.string().unwrap().as_str());
         self
     }
 }
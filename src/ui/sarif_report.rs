@@ -0,0 +1,51 @@
+use crate::crawler::security_finding::{SecurityFinding, SecurityRule};
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+pub fn write(path: &str, findings: &[SecurityFinding]) -> io::Result<()> {
+    let sarif = serde_json::to_string_pretty(&render(findings))?;
+    fs::write(path, sarif)
+}
+
+fn render(findings: &[SecurityFinding]) -> Value {
+    let mut rules: Vec<&SecurityRule> = vec![];
+    for finding in findings {
+        if !rules.iter().any(|r| r.id == finding.rule.id) {
+            rules.push(finding.rule);
+        }
+    }
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "rinzler",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/seska451/rinzler",
+                    "rules": rules.iter().map(|rule| json!({
+                        "id": rule.id,
+                        "name": rule.name,
+                        "shortDescription": { "text": rule.name },
+                        "fullDescription": { "text": rule.description },
+                        "defaultConfiguration": { "level": rule.level },
+                    })).collect::<Vec<Value>>(),
+                }
+            },
+            "results": findings.iter().map(|finding| json!({
+                "ruleId": finding.rule.id,
+                "level": finding.rule.level,
+                "message": { "text": finding.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": finding.target.url }
+                    }
+                }],
+            })).collect::<Vec<Value>>(),
+        }]
+    })
+}
@@ -0,0 +1,107 @@
+use crate::config::DiffFormat;
+use crate::crawler::crawl_target::CrawlTarget;
+use crate::ui::json_report;
+use colored::Colorize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io;
+
+pub struct ScanDiff {
+    added: Vec<(String, Option<u16>)>,
+    removed: Vec<(String, Option<u16>)>,
+    changed: Vec<(String, Option<u16>, Option<u16>)>,
+}
+
+impl ScanDiff {
+    pub fn load(old: &str, new: &str) -> io::Result<ScanDiff> {
+        Ok(ScanDiff::compare(
+            &json_report::read(old)?,
+            &json_report::read(new)?,
+        ))
+    }
+
+    pub fn compare(old: &[CrawlTarget], new: &[CrawlTarget]) -> ScanDiff {
+        let old = ScanDiff::by_url(old);
+        let new = ScanDiff::by_url(new);
+        let mut diff = ScanDiff {
+            added: vec![],
+            removed: vec![],
+            changed: vec![],
+        };
+        for (url, status) in &new {
+            match old.get(url) {
+                None => diff.added.push((url.clone(), *status)),
+                Some(old_status) if old_status != status => {
+                    diff.changed.push((url.clone(), *old_status, *status))
+                }
+                Some(_) => {}
+            }
+        }
+        for (url, status) in &old {
+            if !new.contains_key(url) {
+                diff.removed.push((url.clone(), *status));
+            }
+        }
+        diff
+    }
+
+    pub fn render(&self, format: DiffFormat) -> String {
+        match format {
+            DiffFormat::Text => self.to_text(),
+            DiffFormat::Json => self.to_json().to_string(),
+        }
+    }
+
+    fn by_url(results: &[CrawlTarget]) -> BTreeMap<String, Option<u16>> {
+        results
+            .iter()
+            .map(|result| (CrawlTarget::normalize_url(&result.url), result.status_code))
+            .collect()
+    }
+
+    fn to_text(&self) -> String {
+        let mut lines = vec![];
+        for (url, status) in &self.added {
+            lines.push(format!("{} {} {}", "+".green(), fmt_status(*status), url));
+        }
+        for (url, status) in &self.removed {
+            lines.push(format!("{} {} {}", "-".red(), fmt_status(*status), url));
+        }
+        for (url, old, new) in &self.changed {
+            lines.push(format!(
+                "{} {} -> {} {}",
+                "~".yellow(),
+                fmt_status(*old),
+                fmt_status(*new),
+                url
+            ));
+        }
+        lines.push(format!(
+            "{} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        ));
+        lines.join("\n")
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "added": self.added.iter()
+                .map(|(url, status)| json!({ "url": url, "status": status }))
+                .collect::<Vec<Value>>(),
+            "removed": self.removed.iter()
+                .map(|(url, status)| json!({ "url": url, "status": status }))
+                .collect::<Vec<Value>>(),
+            "changed": self.changed.iter()
+                .map(|(url, old, new)| json!({ "url": url, "old_status": old, "new_status": new }))
+                .collect::<Vec<Value>>(),
+        })
+    }
+}
+
+fn fmt_status(status: Option<u16>) -> String {
+    status
+        .map(|status| status.to_string())
+        .unwrap_or_else(|| "???".to_string())
+}
@@ -0,0 +1,70 @@
+use crate::crawler::crawl_target::CrawlTarget;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio::time;
+use tracing::{debug, warn};
+
+const MAX_BATCH: usize = 50;
+const MAX_ATTEMPTS: u32 = 3;
+
+pub struct Webhook {
+    sender: UnboundedSender<Value>,
+}
+
+impl Clone for Webhook {
+    fn clone(&self) -> Self {
+        Webhook {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl Webhook {
+    // findings are posted from a background task; it finishes once every Webhook has been dropped
+    pub fn start(url: String, user_agent: &str) -> (Webhook, JoinHandle<()>) {
+        let (sender, receiver) = unbounded_channel();
+        let client = Client::builder()
+            .user_agent(user_agent)
+            .build()
+            .unwrap_or_default();
+        let task = tokio::spawn(Webhook::deliver(url, client, receiver));
+        (Webhook { sender }, task)
+    }
+
+    pub fn notify(&self, finding: &CrawlTarget) {
+        let _ = self.sender.send(finding.to_json());
+    }
+
+    async fn deliver(url: String, client: Client, mut receiver: UnboundedReceiver<Value>) {
+        while let Some(finding) = receiver.recv().await {
+            let mut batch = vec![finding];
+            while batch.len() < MAX_BATCH {
+                match receiver.try_recv() {
+                    Ok(finding) => batch.push(finding),
+                    Err(_) => break,
+                }
+            }
+            Webhook::post(&url, &client, json!({ "findings": batch })).await;
+        }
+    }
+
+    async fn post(url: &str, client: &Client, payload: Value) {
+        for attempt in 1..=MAX_ATTEMPTS {
+            match client.post(url).json(&payload).send().await {
+                Ok(res) if res.status().is_success() => {
+                    debug!("Sent findings to webhook {}", url);
+                    return;
+                }
+                Ok(res) => warn!("Webhook {} answered {}", url, res.status()),
+                Err(why) => warn!("Webhook {} couldn't be reached: {}", url, why),
+            }
+            if attempt < MAX_ATTEMPTS {
+                time::sleep(Duration::from_secs(u64::from(attempt))).await;
+            }
+        }
+        warn!("Gave up sending findings to webhook {}", url);
+    }
+}
@@ -0,0 +1,279 @@
+mod common;
+
+use common::{rnz, stderr_of, stdout_of, MockServer, Request, Response};
+use md5::{Digest, Md5};
+use std::collections::HashMap;
+use std::fs;
+
+#[test]
+fn range_is_only_sent_with_get() {
+    // some servers refuse a HEAD carrying a Range outright
+    let server = MockServer::start(|request| {
+        let ranged = request.header("range").is_some();
+        match (request.method.as_str(), ranged, request.path.as_str()) {
+            ("HEAD", true, _) => Response::new(416),
+            ("GET", true, "/") => Response::new(206)
+                .header("Content-Type", "text/html")
+                .body(b"<a href=\"/linked\">linked</a>"),
+            (_, _, "/" | "/linked" | "/hidden") => Response::html("found"),
+            _ => Response::new(404),
+        }
+    });
+    let crawled = rnz(&["--host", &server.url("/"), "--urls-only", "--no-preflight"]);
+    let wordlist = std::env::temp_dir().join(format!("rnz-range-{}.txt", server.port()));
+    fs::write(&wordlist, "hidden\n").unwrap();
+    let browsed = rnz(&[
+        "--host",
+        &server.url("/"),
+        "--wordlist",
+        wordlist.to_str().unwrap(),
+        "--urls-only",
+        "--no-preflight",
+    ]);
+    let _ = fs::remove_file(&wordlist);
+
+    assert!(crawled.status.success());
+    assert!(browsed.status.success());
+    server.with_requests(|requests| {
+        for request in requests.iter().filter(|request| request.method == "HEAD") {
+            assert_eq!(request.header("range"), None, "HEAD {}", request.path);
+        }
+        // the page is still previewed with a partial GET
+        assert!(requests
+            .iter()
+            .any(|request| request.method == "GET" && request.header("range").is_some()));
+    });
+    assert!(stdout_of(&crawled).contains(&server.url("/linked")));
+    assert!(stdout_of(&browsed).contains(&server.url("/hidden")));
+}
+
+#[test]
+fn credentials_in_the_target_url_are_sent_as_basic_auth() {
+    // "user:pa@ss", the @ percent-encoded in the URL
+    let server = MockServer::start(|request| {
+        if request.header("authorization") != Some("Basic dXNlcjpwYUBzcw==") {
+            return Response::new(401).header("WWW-Authenticate", "Basic realm=\"mock\"");
+        }
+        match request.path.as_str() {
+            "/" => Response::html("<a href=\"/private\">private</a>"),
+            _ => Response::html("behind the login"),
+        }
+    });
+    let target = server.url("/").replace("http://", "http://user:pa%40ss@");
+
+    let output = rnz(&["--host", &target, "--urls-only", "--no-preflight"]);
+    let printed = format!("{}{}", stdout_of(&output), stderr_of(&output));
+
+    assert!(output.status.success());
+    assert_eq!(server.hits("GET", "/private"), 1);
+    server.with_requests(|requests| {
+        assert!(requests
+            .iter()
+            .filter(|request| request.path == "/private")
+            .all(|request| request.header("authorization").is_some()));
+    });
+    assert!(stdout_of(&output).contains(&server.url("/private")));
+    // the credentials are stripped from whatever's printed
+    assert!(!printed.contains("pa%40ss"));
+    assert!(!printed.contains("user:"));
+}
+
+#[test]
+fn thousands_of_requests_reuse_a_few_connections() {
+    const WORDS: usize = 3000;
+    const THREADS: usize = 20;
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/" => Response::html("home"),
+        _ => Response::new(404),
+    });
+    let wordlist = std::env::temp_dir().join(format!("rnz-pool-{}.txt", server.port()));
+    let words: Vec<String> = (0..WORDS).map(|n| format!("word{}", n)).collect();
+    fs::write(&wordlist, words.join("\n")).unwrap();
+
+    let output = rnz(&[
+        "--host",
+        &server.url("/"),
+        "--wordlist",
+        wordlist.to_str().unwrap(),
+        "--threads",
+        &THREADS.to_string(),
+        "--urls-only",
+        "--no-preflight",
+    ]);
+    let _ = fs::remove_file(&wordlist);
+
+    assert!(output.status.success());
+    assert!(!stderr_of(&output).contains("Cannot assign requested address"));
+    assert_eq!(
+        server.with_requests(|requests| {
+            requests
+                .iter()
+                .filter(|request| request.path.starts_with("/word"))
+                .count()
+        }),
+        WORDS
+    );
+    // kept alive, a connection carries many requests; one per request would run out of ports
+    assert!(
+        server.connection_count() <= 2 * THREADS,
+        "{} connections for {} requests",
+        server.connection_count(),
+        server.request_count()
+    );
+}
+
+#[test]
+fn a_post_redirected_with_303_becomes_a_get() {
+    let elsewhere = MockServer::start(|_| Response::html("landed"));
+    let (seen, kept) = (elsewhere.url("/seen"), elsewhere.url("/kept"));
+    let server = MockServer::start(move |request| match request.path.as_str() {
+        "/" => Response::html("home"),
+        "/form" => Response::redirect(303, &seen),
+        "/keep" => Response::redirect(307, &kept),
+        _ => Response::new(404),
+    });
+    let wordlist = std::env::temp_dir().join(format!("rnz-post-{}.txt", server.port()));
+    fs::write(&wordlist, "form\nkeep\n").unwrap();
+
+    let output = rnz(&[
+        "--host",
+        &server.url("/"),
+        "--wordlist",
+        wordlist.to_str().unwrap(),
+        "--data",
+        "user=admin",
+        "--urls-only",
+        "--no-preflight",
+    ]);
+    let _ = fs::remove_file(&wordlist);
+
+    assert!(output.status.success());
+    assert!(server.hits("POST", "/form") >= 1);
+    elsewhere.with_requests(|requests| {
+        for request in requests.iter().filter(|request| request.path == "/seen") {
+            assert_eq!(request.method, "GET");
+            assert_eq!(request.header("content-type"), None);
+            assert_eq!(request.header("content-length"), None);
+        }
+        // only a 307 or 308 repeats the POST and its body
+        for request in requests.iter().filter(|request| request.path == "/kept") {
+            assert_eq!(request.method, "POST");
+            assert_eq!(request.header("content-length"), Some("10"));
+        }
+    });
+    assert!(elsewhere.requested("/seen"));
+    assert!(elsewhere.requested("/kept"));
+}
+
+#[test]
+fn the_host_header_is_only_sent_to_the_target() {
+    let elsewhere = MockServer::start(|_| Response::html("landed"));
+    let landing = elsewhere.url("/landing");
+    let server = MockServer::start(move |request| match request.path.as_str() {
+        "/" => Response::html("<a href=\"/away\">away</a>"),
+        "/away" => Response::redirect(302, &landing),
+        _ => Response::new(404),
+    });
+
+    let output = rnz(&[
+        "--host",
+        &server.url("/"),
+        "--host-header",
+        "intranet.example",
+        "--urls-only",
+    ]);
+
+    assert!(output.status.success());
+    server.with_requests(|requests| {
+        assert!(requests
+            .iter()
+            .all(|request| request.header("host") == Some("intranet.example")));
+    });
+    assert!(elsewhere.requested("/landing"));
+    let own_host = elsewhere.url("").replace("http://", "");
+    elsewhere.with_requests(|requests| {
+        for request in requests {
+            assert_eq!(request.header("host"), Some(own_host.as_str()));
+        }
+    });
+}
+
+fn md5_hex(data: &str) -> String {
+    format!("{:x}", Md5::digest(data.as_bytes()))
+}
+
+// checks the answer to the challenge the same way a server would
+fn answers_digest(request: &Request, nonce: &str) -> bool {
+    let params: HashMap<&str, &str> = match request.header("authorization") {
+        Some(authorization) => match authorization.strip_prefix("Digest ") {
+            Some(params) => params
+                .split(", ")
+                .filter_map(|param| param.split_once('='))
+                .map(|(name, value)| (name, value.trim_matches('"')))
+                .collect(),
+            None => return false,
+        },
+        None => return false,
+    };
+    let param = |name: &str| params.get(name).copied().unwrap_or_default();
+    let ha1 = md5_hex("user:mock:secret");
+    let ha2 = md5_hex(&format!("{}:{}", request.method, param("uri")));
+    let expected = md5_hex(&format!(
+        "{}:{}:{}:{}:auth:{}",
+        ha1,
+        nonce,
+        param("nc"),
+        param("cnonce"),
+        ha2
+    ));
+    param("nonce") == nonce && param("uri") == request.path && param("response") == expected
+}
+
+#[test]
+fn answers_a_digest_challenge_and_sends_the_request_again() {
+    const NONCE: &str = "dcd98b7102dd2f0e8b11d0f600bfb0c093";
+    let server = MockServer::start(|request| {
+        if !answers_digest(request, NONCE) {
+            return Response::new(401).header(
+                "WWW-Authenticate",
+                &format!("Digest realm=\"mock\", qop=\"auth\", nonce=\"{}\"", NONCE),
+            );
+        }
+        match request.path.as_str() {
+            "/" => Response::html("<a href=\"/private\">private</a>"),
+            _ => Response::html("behind the login"),
+        }
+    });
+    let target = server.url("/").replace("http://", "http://user:secret@");
+    let trace = std::env::temp_dir().join(format!("rnz-digest-{}.jsonl", server.port()));
+
+    let output = rnz(&[
+        "--host",
+        &target,
+        "--auth-type",
+        "digest",
+        "--trace-http",
+        trace.to_str().unwrap(),
+        "--urls-only",
+        "--no-preflight",
+    ]);
+    let traced = fs::read_to_string(&trace).unwrap_or_default();
+    let _ = fs::remove_file(&trace);
+
+    assert!(output.status.success());
+    assert!(stdout_of(&output).contains(&server.url("/private")));
+    // the resent request goes out like any other, so it's traced too
+    assert_eq!(traced.lines().count(), server.request_count());
+    server.with_requests(|requests| {
+        // the first request is challenged, and answered by sending it again
+        let first = &requests[0];
+        assert_eq!(first.header("authorization"), None);
+        assert_eq!(requests[1].path, first.path);
+        assert!(answers_digest(&requests[1], NONCE));
+        // the challenge is kept, so later requests answer it up front
+        assert!(requests
+            .iter()
+            .filter(|request| request.path == "/private")
+            .all(|request| answers_digest(request, NONCE)));
+    });
+}
@@ -0,0 +1,273 @@
+// each test binary only uses some of these helpers
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::process::{Child, ChildStdin, Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// long enough for any scan these tests run, short enough that a hang fails rather than stalls
+const RNZ_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(status: u16) -> Response {
+        Response {
+            status,
+            headers: vec![],
+            body: vec![],
+        }
+    }
+
+    pub fn html(body: &str) -> Response {
+        Response::new(200)
+            .header("Content-Type", "text/html")
+            .body(body.as_bytes())
+    }
+
+    pub fn redirect(status: u16, location: &str) -> Response {
+        Response::new(status).header("Location", location)
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Response {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body(mut self, body: &[u8]) -> Response {
+        self.body = body.to_vec();
+        self
+    }
+}
+
+type Handler = dyn Fn(&Request) -> Response + Send + Sync;
+
+/// A plain HTTP/1.1 server on a local port that answers with `handler` and remembers what it
+/// was asked for, so a test can check what rinzler requested.
+pub struct MockServer {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<Request>>>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl MockServer {
+    pub fn start<F>(handler: F) -> MockServer
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        MockServer::start_on("127.0.0.1:0", handler)
+    }
+
+    pub fn start_on<F>(bind: &str, handler: F) -> MockServer
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind(bind).expect("mock server couldn't bind");
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(vec![]));
+        let connections = Arc::new(AtomicUsize::new(0));
+        let handler: Arc<Handler> = Arc::new(handler);
+        {
+            let requests = Arc::clone(&requests);
+            let connections = Arc::clone(&connections);
+            thread::spawn(move || {
+                for stream in listener.incoming().map_while(Result::ok) {
+                    connections.fetch_add(1, Ordering::SeqCst);
+                    let requests = Arc::clone(&requests);
+                    let handler = Arc::clone(&handler);
+                    thread::spawn(move || serve(stream, &*handler, &requests));
+                }
+            });
+        }
+        MockServer {
+            addr,
+            requests,
+            connections,
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// How many times `path` was requested with `method`.
+    pub fn hits(&self, method: &str, path: &str) -> usize {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.method == method && request.path == path)
+            .count()
+    }
+
+    pub fn requested(&self, path: &str) -> bool {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|request| request.path == path)
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    pub fn connection_count(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+
+    pub fn with_requests<T>(&self, f: impl FnOnce(&[Request]) -> T) -> T {
+        f(&self.requests.lock().unwrap())
+    }
+}
+
+// requests on one connection are answered in turn until the client closes it
+fn serve(stream: TcpStream, handler: &Handler, requests: &Mutex<Vec<Request>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let path = parts.next().unwrap_or_default().to_string();
+        let mut headers = HashMap::new();
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).unwrap_or(0) == 0 {
+                return;
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+        }
+        let length = headers
+            .get("content-length")
+            .and_then(|length| length.parse().ok())
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        if reader.read_exact(&mut body).is_err() {
+            return;
+        }
+
+        let request = Request {
+            method,
+            path,
+            headers,
+        };
+        let response = handler(&request);
+        let head_only = request.method == "HEAD";
+        requests.lock().unwrap().push(request);
+
+        let mut out = format!("HTTP/1.1 {} Mock\r\n", response.status);
+        for (name, value) in &response.headers {
+            out.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        out.push_str(&format!("Content-Length: {}\r\n\r\n", response.body.len()));
+        let mut out = out.into_bytes();
+        if !head_only {
+            out.extend_from_slice(&response.body);
+        }
+        if writer.write_all(&out).is_err() {
+            return;
+        }
+    }
+}
+
+/// Runs the rnz binary to completion with nothing on stdin, killing it if it hangs.
+pub fn rnz(args: &[&str]) -> Output {
+    wait_for(spawn(args, Stdio::null()), args)
+}
+
+/// Runs the rnz binary to completion while `typing` writes to its stdin from another thread, as
+/// someone at the console would. Stdin is closed once `typing` returns.
+pub fn rnz_typing<F>(args: &[&str], typing: F) -> Output
+where
+    F: FnOnce(&mut ChildStdin) + Send + 'static,
+{
+    let mut child = spawn(args, Stdio::piped());
+    let mut stdin = child.stdin.take().unwrap();
+    thread::spawn(move || typing(&mut stdin));
+    wait_for(child, args)
+}
+
+fn spawn(args: &[&str], stdin: Stdio) -> Child {
+    Command::new(env!("CARGO_BIN_EXE_rnz"))
+        .args(args)
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("rnz couldn't be started")
+}
+
+fn wait_for(mut child: Child, args: &[&str]) -> Output {
+    let started = Instant::now();
+    // the pipes are drained as it runs, so a chatty scan can't fill them and block
+    let stdout = drain(child.stdout.take().unwrap());
+    let stderr = drain(child.stderr.take().unwrap());
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if started.elapsed() > RNZ_TIMEOUT {
+            let _ = child.kill();
+            panic!("rnz {:?} didn't finish within {:?}", args, RNZ_TIMEOUT);
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+    Output {
+        status,
+        stdout: stdout.join().unwrap(),
+        stderr: stderr.join().unwrap(),
+    }
+}
+
+fn drain(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut out = vec![];
+        let _ = pipe.read_to_end(&mut out);
+        out
+    })
+}
+
+pub fn stdout_of(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+pub fn stderr_of(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
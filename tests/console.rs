@@ -0,0 +1,58 @@
+mod common;
+
+use common::{rnz_typing, stderr_of, stdout_of, MockServer, Response};
+use std::fs;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn skipping_after_the_target_is_crawled_keeps_the_console_running() {
+    // /slow is only linked from the crawled root page, so asking for it means the root's result is in
+    let crawled = Arc::new(AtomicBool::new(false));
+    let server = {
+        let crawled = Arc::clone(&crawled);
+        MockServer::start(move |request| match request.path.as_str() {
+            "/" => Response::html("<a href=\"/slow\">slow</a>"),
+            "/slow" => {
+                crawled.store(true, Ordering::SeqCst);
+                thread::sleep(Duration::from_secs(2));
+                Response::html("finally")
+            }
+            _ => Response::new(404),
+        })
+    };
+
+    let results = std::env::temp_dir().join(format!("rnz-skip-{}.csv", server.port()));
+
+    let output = rnz_typing(
+        &[
+            "--host",
+            &server.url("/"),
+            "--common-checks",
+            "--no-preflight",
+            "--output-csv",
+            results.to_str().unwrap(),
+        ],
+        move |stdin| {
+            while !crawled.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(20));
+            }
+            thread::sleep(Duration::from_millis(200));
+            let _ = stdin.write_all(b"s\n");
+            // held open so the key isn't lost to the console seeing stdin close first
+            thread::sleep(Duration::from_secs(1));
+        },
+    );
+    let printed = format!("{}{}", stdout_of(&output), stderr_of(&output));
+    let recorded = fs::read_to_string(&results).unwrap_or_default();
+    let _ = fs::remove_file(&results);
+
+    assert!(output.status.success());
+    assert!(!printed.contains("panicked"), "{}", printed);
+    assert!(printed.contains("Scan Finished"));
+    // the console was still there to record what finished after the key was pressed
+    assert!(recorded.contains(&server.url("/slow")));
+}
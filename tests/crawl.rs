@@ -0,0 +1,171 @@
+mod common;
+
+use common::{rnz, rnz_typing, stderr_of, stdout_of, MockServer, Response};
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn exits_only_after_every_recursive_crawl_finishes() {
+    // each page links one level deeper and answers slowly, so an early exit would miss the last
+    let server = MockServer::start(|request| {
+        let depth: usize = request.path.trim_start_matches("/d").parse().unwrap_or(0);
+        thread::sleep(Duration::from_millis(100));
+        match depth {
+            0..=4 => Response::html(&format!("<a href=\"/d{}\">deeper</a>", depth + 1)),
+            _ => Response::html("the bottom"),
+        }
+    });
+
+    let output = rnz(&[
+        "--host",
+        &server.url("/d0"),
+        "--urls-only",
+        "--no-preflight",
+    ]);
+    let printed = stdout_of(&output);
+
+    assert!(output.status.success());
+    for depth in 0..=5 {
+        let path = format!("/d{}", depth);
+        assert_eq!(server.hits("GET", &path), 1, "{} wasn't crawled once", path);
+        assert!(
+            printed.contains(&server.url(&path)),
+            "{} wasn't reported",
+            path
+        );
+    }
+}
+
+#[test]
+fn does_not_crawl_past_a_redirect_out_of_scope() {
+    let elsewhere = MockServer::start(|request| match request.path.as_str() {
+        "/landing" => Response::html("<a href=\"/deeper\">deeper</a>"),
+        _ => Response::html("nothing here"),
+    });
+    // the same server under another name is another host as far as scope goes
+    let landing = format!("http://localhost:{}/landing", elsewhere.port());
+    let server = MockServer::start(move |request| match request.path.as_str() {
+        "/" => Response::html("<a href=\"/away\">away</a>"),
+        "/away" => Response::redirect(302, &landing),
+        _ => Response::new(404),
+    });
+
+    let output = rnz(&["--host", &server.url("/"), "--urls-only", "--no-preflight"]);
+    let printed = stdout_of(&output);
+
+    assert!(output.status.success());
+    assert_eq!(server.hits("HEAD", "/away"), 1);
+    assert_eq!(elsewhere.hits("GET", "/landing"), 0);
+    assert!(!elsewhere.requested("/deeper"));
+    assert!(!printed.contains("/deeper"));
+}
+
+#[test]
+fn follows_accented_links_on_a_latin1_page() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/" => Response::new(200)
+            .header("Content-Type", "text/html; charset=iso-8859-1")
+            .body(b"<a href=\"/caf\xe9.html\">caf\xe9</a> <a href=\"/na\xefve.html\">na\xefve</a>"),
+        _ => Response::html("accented"),
+    });
+
+    let output = rnz(&["--host", &server.url("/"), "--urls-only", "--no-preflight"]);
+
+    assert!(output.status.success());
+    // the links are requested as their UTF-8 percent-encoding, not mangled Latin-1 bytes
+    assert_eq!(server.hits("GET", "/caf%C3%A9.html"), 1);
+    assert_eq!(server.hits("GET", "/na%C3%AFve.html"), 1);
+}
+
+#[test]
+fn crawls_an_ipv6_target_and_stays_in_scope() {
+    let server = MockServer::start_on("[::1]:0", |request| {
+        let port = request
+            .header("host")
+            .and_then(|host| host.rsplit_once(':'))
+            .map(|(_, port)| port.to_string())
+            .unwrap_or_default();
+        match request.path.as_str() {
+            "/" => Response::html(&format!(
+                "<a href=\"/a\">a</a><a href=\"http://[::1]:{0}/b\">b</a><a href=\"http://127.0.0.1:{0}/c\">c</a>",
+                port
+            )),
+            _ => Response::html("leaf"),
+        }
+    });
+
+    // the IPv4 loopback on the same port is another host, so it should never be asked for anything
+    let ipv4 = MockServer::start_on(&format!("127.0.0.1:{}", server.port()), |_| {
+        Response::html("out of scope")
+    });
+
+    let output = rnz(&["--host", &server.url("/"), "--urls-only", "--no-preflight"]);
+    let printed = stdout_of(&output);
+
+    assert!(output.status.success());
+    assert!(server.url("/").starts_with("http://[::1]:"));
+    assert_eq!(server.hits("GET", "/a"), 1);
+    assert_eq!(server.hits("GET", "/b"), 1);
+    assert!(printed.contains(&server.url("/a")));
+    assert!(printed.contains(&server.url("/b")));
+    assert_eq!(ipv4.request_count(), 0);
+    assert!(!printed.contains("127.0.0.1"));
+}
+
+#[test]
+fn runs_the_common_checks_on_a_target_read_from_stdin() {
+    let server = MockServer::start(|request| {
+        if request.header("authorization") != Some("Basic dXNlcjpwYXNz") {
+            return Response::new(401).header("WWW-Authenticate", "Basic realm=\"mock\"");
+        }
+        match request.path.as_str() {
+            "/" | "/.git/HEAD" => Response::html("found"),
+            _ => Response::new(404),
+        }
+    });
+    let target = server.url("/").replace("http://", "http://user:pass@");
+
+    let output = rnz_typing(
+        &[
+            "--stdin-urls",
+            "--common-checks",
+            "--urls-only",
+            "--no-preflight",
+        ],
+        move |stdin| {
+            let _ = writeln!(stdin, "{}", target);
+        },
+    );
+    let printed = format!("{}{}", stdout_of(&output), stderr_of(&output));
+
+    assert!(output.status.success());
+    assert!(server.requested("/.git/config"));
+    assert!(stdout_of(&output).contains(&server.url("/.git/HEAD")));
+    // like a --host target, its credentials become auth and are stripped from what's printed
+    assert!(!printed.contains("user:pass"));
+}
+
+#[test]
+fn fails_on_a_status_that_was_found() {
+    let server = MockServer::start(|request| match request.path.as_str() {
+        "/" => Response::html("<a href=\"/broken\">broken</a>"),
+        _ => Response::new(500),
+    });
+    let scan = |fail_on: &str| {
+        rnz(&[
+            "--host",
+            &server.url("/"),
+            "--urls-only",
+            "--no-preflight",
+            "--fail-on",
+            fail_on,
+        ])
+    };
+
+    assert_eq!(scan("status:500-599").status.code(), Some(2));
+    assert_eq!(scan("status:200").status.code(), Some(2));
+    assert_eq!(scan("status:403,404").status.code(), Some(0));
+    assert_eq!(scan("2").status.code(), Some(2));
+    assert_eq!(scan("count:3").status.code(), Some(0));
+}
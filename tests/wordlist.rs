@@ -0,0 +1,19 @@
+mod common;
+
+use common::{rnz, stderr_of, stdout_of};
+
+#[test]
+fn missing_wordlist_exits_cleanly() {
+    let output = rnz(&[
+        "--host",
+        "http://127.0.0.1:1/",
+        "--wordlist",
+        "/nonexistent/words.txt",
+        "--no-preflight",
+    ]);
+    let printed = format!("{}{}", stdout_of(&output), stderr_of(&output));
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(printed.contains("wordlist '/nonexistent/words.txt' not found"));
+    assert!(!printed.contains("panicked"));
+}